@@ -0,0 +1,114 @@
+//! Derive macro for the `CheapClone` trait of [`clone-macro`].
+//!
+//! This crate is re-exported by `clone-macro` when its `derive` feature is
+//! enabled, so it usually doesn't need to be depended on directly.
+//!
+//! [`clone-macro`]: https://docs.rs/clone-macro
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields};
+
+/// Implements `CheapClone` for a struct or enum, as long as every field is
+/// `CheapClone` too. The type still needs to implement [`Clone`], usually by
+/// deriving it alongside:
+/// ```rust
+/// use clone_macro::{clone, CheapClone};
+/// use std::{cell::Cell, rc::Rc};
+///
+/// #[derive(Clone, CheapClone)]
+/// struct Handle {
+///     clicks: Rc<Cell<u32>>,
+///     id: u32,
+/// }
+///
+/// let handle = Handle { clicks: Rc::default(), id: 1 };
+///
+/// let c = clone!(cheap [handle], move || handle.clicks.set(handle.id));
+///
+/// c();
+///
+/// assert_eq!(handle.clicks.get(), 1);
+/// ```
+///
+/// Generic handles can be derived for as well, even though the type parameter
+/// is only cheap to clone behind the pointer. Like `Clone`, the handle is then
+/// only `CheapClone` for type parameters which are `Clone`:
+/// ```rust
+/// use clone_macro::{clone, CheapClone};
+/// use std::rc::Rc;
+///
+/// #[derive(Clone, CheapClone)]
+/// struct Handle<T>(Rc<T>);
+///
+/// struct NotClone;
+///
+/// let _handle = Handle(Rc::new(NotClone));
+///
+/// let handle = Handle(Rc::new("Ferris".to_string()));
+///
+/// let c = clone!(cheap [handle], move || handle.0.len());
+///
+/// assert_eq!(c(), 6);
+/// ```
+///
+/// Fields which are deep cloned are rejected:
+/// ```rust,compile_fail
+/// use clone_macro::CheapClone;
+///
+/// #[derive(Clone, CheapClone)]
+/// struct Handle {
+///     names: Vec<String>,
+/// }
+/// ```
+#[proc_macro_derive(CheapClone)]
+pub fn derive_cheap_clone(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields: Vec<&Fields> = match &input.data {
+        Data::Struct(data) => vec![&data.fields],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| &variant.fields)
+            .collect(),
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "`CheapClone` can't be derived for unions",
+            ))
+        }
+    };
+
+    // Every field has to be cheap to clone for the whole type to be, so the
+    // field types are bounded directly rather than the type parameters. The
+    // type itself has to be `Clone` as well, which `#[derive(Clone)]` only
+    // provides when every type parameter is `Clone`, even for parameters
+    // which are only used behind a pointer such as `Rc<T>`.
+    let bounds = fields
+        .into_iter()
+        .flatten()
+        .map(|field| -> syn::WherePredicate {
+            let ty = &field.ty;
+
+            parse_quote!(#ty: ::clone_macro::CheapClone)
+        })
+        .chain([parse_quote!(Self: ::core::clone::Clone)])
+        .collect::<Vec<_>>();
+
+    input.generics.make_where_clause().predicates.extend(bounds);
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::clone_macro::CheapClone for #ident #ty_generics #where_clause {}
+    })
+}
@@ -0,0 +1,106 @@
+//! The [`CheapClone`] trait used by `cheap` capture lists.
+
+use std::{
+    marker::PhantomData,
+    rc,
+    sync::{self, mpsc},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Marker for types which are cheap to clone, such as reference counted
+/// pointers, channel senders and small [`Copy`] types, as well as tuples and
+/// arrays of them.
+///
+/// When a capture list is prefixed with `cheap`, `clone!` clones every capture
+/// through [`CheapClone::cheap_clone`], so accidentally capturing something
+/// which would be deep cloned fails to compile.
+///
+/// Implementing it for your own handle types is a one-liner:
+/// ```rust
+/// use clone_macro::CheapClone;
+/// use std::rc::Rc;
+///
+/// #[derive(Clone)]
+/// struct Handle(Rc<String>);
+///
+/// impl CheapClone for Handle {}
+/// ```
+///
+/// With the `derive` feature enabled, it can also be derived for structs and
+/// enums whose fields are all `CheapClone`, as in
+/// `#[derive(Clone, CheapClone)]`.
+pub trait CheapClone: Clone {
+    /// Clones the value. This should never be overridden to do anything
+    /// other than [`Clone::clone`].
+    fn cheap_clone(&self) -> Self {
+        self.clone()
+    }
+}
+
+macro_rules! impl_cheap_clone {
+    ($($ty:ty),* $(,)?) => {
+        $(impl CheapClone for $ty {})*
+    };
+}
+
+impl_cheap_clone!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    Duration,
+    Instant,
+    SystemTime,
+);
+
+macro_rules! impl_cheap_clone_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: CheapClone),+> CheapClone for ($($name,)+) {}
+    };
+}
+
+impl_cheap_clone_tuple!(A);
+impl_cheap_clone_tuple!(A B);
+impl_cheap_clone_tuple!(A B C);
+impl_cheap_clone_tuple!(A B C D);
+impl_cheap_clone_tuple!(A B C D E);
+impl_cheap_clone_tuple!(A B C D E F);
+impl_cheap_clone_tuple!(A B C D E F G);
+impl_cheap_clone_tuple!(A B C D E F G H);
+impl_cheap_clone_tuple!(A B C D E F G H I);
+impl_cheap_clone_tuple!(A B C D E F G H I J);
+impl_cheap_clone_tuple!(A B C D E F G H I J K);
+impl_cheap_clone_tuple!(A B C D E F G H I J K L);
+
+impl<T: CheapClone, const N: usize> CheapClone for [T; N] {}
+
+impl<T: ?Sized> CheapClone for &T {}
+
+impl<T: ?Sized> CheapClone for PhantomData<T> {}
+
+impl<T: CheapClone> CheapClone for Option<T> {}
+
+impl<T: ?Sized> CheapClone for rc::Rc<T> {}
+
+impl<T: ?Sized> CheapClone for rc::Weak<T> {}
+
+impl<T: ?Sized> CheapClone for sync::Arc<T> {}
+
+impl<T: ?Sized> CheapClone for sync::Weak<T> {}
+
+impl<T> CheapClone for mpsc::Sender<T> {}
+
+impl<T> CheapClone for mpsc::SyncSender<T> {}
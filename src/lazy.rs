@@ -0,0 +1,64 @@
+//! The [`Lazy`] wrapper used by the `lazy` capture form.
+
+use std::{ops::Deref, sync::OnceLock};
+
+/// A handle whose pointee is only cloned the first time it is needed.
+///
+/// `clone!` uses this type for `lazy` captures, so that the potentially
+/// expensive clone is deferred until the closure first runs, and is then
+/// reused by every subsequent call:
+/// ```rust
+/// use clone_macro::clone;
+/// use std::{cell::Cell, rc::Rc};
+///
+/// struct Big {
+///     clones: Rc<Cell<usize>>,
+/// }
+///
+/// impl Clone for Big {
+///     fn clone(&self) -> Self {
+///         self.clones.set(self.clones.get() + 1);
+///
+///         Self { clones: Rc::clone(&self.clones) }
+///     }
+/// }
+///
+/// let clones = Rc::new(Cell::new(0));
+/// let big = Rc::new(Big { clones: Rc::clone(&clones) });
+///
+/// let c = clone!([lazy big], move || big.clones.get());
+///
+/// assert_eq!(clones.get(), 0);
+///
+/// c();
+/// c();
+///
+/// assert_eq!(clones.get(), 1);
+/// ```
+pub struct Lazy<P>
+where
+    P: Deref,
+    P::Target: Sized,
+{
+    handle: P,
+    value: OnceLock<P::Target>,
+}
+
+impl<P> Lazy<P>
+where
+    P: Deref,
+    P::Target: Clone + Sized,
+{
+    /// Wraps `handle` without cloning its pointee.
+    pub fn new(handle: P) -> Self {
+        Self {
+            handle,
+            value: OnceLock::new(),
+        }
+    }
+
+    /// Returns the cloned pointee, cloning it first if this is the first call.
+    pub fn get(&self) -> &P::Target {
+        self.value.get_or_init(|| (*self.handle).clone())
+    }
+}
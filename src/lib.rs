@@ -1,7 +1,11 @@
 //! A simple macro to make cloning data before passing it into a `move` closure or block.
 //!
 //! This macro is intentionally designed to be compatible with
-//! `rustfmt` formatting.
+//! `rustfmt` formatting, as long as every capture in the list is also valid
+//! Rust syntax on its own, such as `a`, `&a` or `{ a.len() } as len`. Prefixed
+//! forms like `weak a` or `move a`, typed captures and directives make rustfmt
+//! leave the whole call, including its body, unformatted. Modes in front of
+//! the list are formatted as if the list was indexing them, as in `cheap[a]`.
 //!
 //! You can use this macro throughout your crate without needing to explicitly
 //! import it every time as follows:
@@ -22,68 +26,948 @@
 //! clone!(/* ... */);
 //! ```
 //!
+//! It can also be called by its full path, without importing it at all, as in
+//! `clone_macro::clone!(/* ... */)`.
+//!
 //! # Syntax
-//! The `clone!` macro takes a comma separated list of either one of two forms
-//! which can have an optional `mut` prefix modifier, followed by an arbitrary
-//! expression.
+//! The `clone!` macro takes a comma separated list of captures, followed by an
+//! arbitrary expression. The two basic forms of capture, described first, can
+//! have an optional `mut` prefix modifier. The sections after them describe the
+//! other forms, and the modes which can be put in front of the list.
+//!
+//! For example, the following is a valid call
+//! ```rust
+//! # use clone_macro::clone;
+//!
+//! let a = 1;
+//! let b = 2;
+//!
+//! clone!([mut a, b], ());
+//! ```
+//!
+//! and desugars down to:
+//! ```rust
+//! let a = 1;
+//! let b = 2;
+//!
+//! {
+//!     let mut a = a.clone();
+//!     let b = b.clone();
+//!
+//!     ()
+//! };
+//! ```
+//!
+//! The clone list can also take a second form, which is an arbitrary expression
+//! followed by `as` and the name of the variable. For example:
+//! ```rust
+//! # use clone_macro::clone;
+//!
+//! let s = "Hello, there!";
+//!
+//! clone!([{ s.len() } as len], move || {
+//!     assert_eq!(len, "Hello, there!".len());
+//! });
+//! ```
+//!
+//! The above desugars into:
+//! ```rust
+//! # use clone_macro::clone;
+//!
+//! let s = "Hello, there!";
+//!
+//! {
+//!     let len = "Hello, there!".len();
+//!
+//!     move || {
+//!         assert_eq!(len, "Hello, there!".len());
+//!     }
+//! };
+//! ```
+//!
+//! The braces can be left out, as long as the expression itself doesn't contain
+//! an `as` cast, since the expression ends at the first `as`, or a comma outside
+//! of any parentheses or brackets, since that ends the capture. Commas in
+//! turbofish generics are such commas, so `HashMap::<u8, u8>::new()` needs
+//! braces too:
+//! ```rust
+//! # use clone_macro::clone;
+//! use std::collections::HashMap;
+//!
+//! let s = "Hello, there!";
+//! let v = vec![1, 2, 3];
+//!
+//! clone!([s.len() as len, v[0] as first, { HashMap::<u8, u8>::new() } as map], move || {
+//!     assert_eq!(len, 13);
+//!     assert_eq!(first, 1);
+//!     assert!(map.is_empty());
+//! });
+//! ```
+//!
+//! Renaming an identifier is common enough that it has its own shorthand, so
+//! `sender as tx` clones `sender` into a variable named `tx`, and
+//! `mut state as local_state` does the same with a mutable binding:
+//! ```rust
+//! # use clone_macro::clone;
+//! use std::sync::mpsc;
+//!
+//! let (sender, receiver) = mpsc::channel();
+//! let state = vec![1, 2];
+//!
+//! let c = clone!([sender as tx, mut state as local_state], move || {
+//!     local_state.push(3);
+//!     tx.send(local_state).unwrap();
+//! });
+//!
+//! c();
+//!
+//! assert_eq!(receiver.recv().unwrap(), [1, 2, 3]);
+//! assert_eq!(state, [1, 2]);
+//! ```
+//!
+//! The new name can be given a type, as in `name as label: Arc<str>`, and
+//! since a renamed identifier is just an expression without braces, it can
+//! also be destructured:
+//! ```rust
+//! # use clone_macro::clone;
+//! use std::sync::Arc;
+//!
+//! #[derive(Clone)]
+//! struct Config {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! #[derive(Clone)]
+//! struct Id(u32);
+//!
+//! let name = "Ferris".to_string();
+//! let config = Config { host: "localhost".to_string(), port: 8080 };
+//! let id = Id(7);
+//!
+//! let c = clone!(
+//!     [name as label: Arc<str>, config as Config { host, .. }, id as Id(raw)],
+//!     move || format!("{label}@{host}#{raw}"),
+//! );
+//!
+//! assert_eq!(c(), "Ferris@localhost#7");
+//! assert_eq!(config.port, 8080);
+//! ```
+//!
+//! This macro is most useful when the second argument is a closure, and is what
+//! it is intended to work with, though not strictly so.
+//!
+//! All forms mentioned above can be mixed and matched, including adding a `mut` modifier
+//! for the second form as:
+//! ```rust,ignore
+//! mut { $expr } as $ident
+//! ```
+//!
+//! Closures and `async` blocks are always turned into `move` closures and
+//! blocks, since otherwise they would only borrow the clones, so `move` can be
+//! left out:
+//! ```rust
+//! # use clone_macro::clone;
+//! let name = "Ferris".to_string();
+//!
+//! let greet = clone!([name], || format!("Hello, {name}!"));
+//!
+//! drop(name);
+//!
+//! assert_eq!(greet(), "Hello, Ferris!");
+//! ```
+//!
+//! Keep in mind that this also moves anything else the body uses, so values
+//! which should only be borrowed need to be captured with `&`, as described
+//! in [Borrowed Captures](#borrowed-captures).
+//!
+//! ## Destructuring
+//! The name after `as` can be any irrefutable pattern, which is handy for
+//! splitting a cloned value into its parts:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! struct Config {
+//!     host: String,
+//!     port: u16,
+//!     verbose: bool,
+//! }
+//!
+//! let config = Config {
+//!     host: "localhost".to_string(),
+//!     port: 8080,
+//!     verbose: false,
+//! };
+//! let pair = (1, "one");
+//! let list = [1, 2, 3];
+//!
+//! let c = clone!(
+//!     [
+//!         { (config.host, config.port) } as (host, port),
+//!         { pair } as (number, _),
+//!         { list } as [first, ..],
+//!     ],
+//!     move || format!("{host}:{port} {number} {first}"),
+//! );
+//!
+//! assert_eq!(c(), "localhost:8080 1 1");
+//! ```
+//!
+//! A pattern can't be made mutable as a whole, so `mut` goes on the bindings
+//! inside it instead, as in `{ pair } as (mut number, name)`:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! let pair = (1, "one");
+//!
+//! clone!([mut { pair } as (number, name)], move || number + name.len());
+//! ```
+//!
+//! The same goes for struct patterns:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! # #[derive(Clone)]
+//! # struct Config {
+//! #     host: String,
+//! #     port: u16,
+//! # }
+//! let cfg = Config { host: "localhost".to_string(), port: 8080 };
+//!
+//! clone!([mut { cfg } as Config { host, .. }], move || host.push('!'));
+//! ```
+//!
+//! ## Field Captures
+//! Fields can be captured directly, in which case the clone is bound to a
+//! variable named after the last field in the path:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! struct Config {
+//!     timeout: u64,
+//! }
+//!
+//! struct Server {
+//!     config: Config,
+//!     name: String,
+//! }
+//!
+//! impl Server {
+//!     fn describe(&self) -> impl Fn() -> String {
+//!         clone!([self.config.timeout, self.name], move || {
+//!             format!("{name} times out after {timeout}s")
+//!         })
+//!     }
+//! }
+//!
+//! let server = Server {
+//!     config: Config { timeout: 30 },
+//!     name: "api".to_string(),
+//! };
+//!
+//! assert_eq!(server.describe()(), "api times out after 30s");
+//! ```
+//!
+//! The list above desugars into:
+//! ```rust,ignore
+//! let timeout = self.config.timeout.clone();
+//! let name = self.name.clone();
+//! ```
+//!
+//! ## Capturing `self`
+//! Since `self` cannot be rebound, it has to be given a new name when it is
+//! captured, as in `self as this`. This is particularly handy for cheaply
+//! clonable types which register callbacks from their own methods:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{cell::Cell, rc::Rc};
+//!
+//! #[derive(Clone, Default)]
+//! struct Counter {
+//!     count: Rc<Cell<u32>>,
+//! }
+//!
+//! impl Counter {
+//!     fn increment(&self) {
+//!         self.count.set(self.count.get() + 1);
+//!     }
+//!
+//!     fn on_click(&self) -> impl Fn() {
+//!         clone!([self as this], move || this.increment())
+//!     }
+//! }
+//!
+//! let counter = Counter::default();
+//!
+//! counter.on_click()();
+//!
+//! assert_eq!(counter.count.get(), 1);
+//! ```
+//!
+//! Like any other rename, the clone can be bound mutably with
+//! `mut self as this`:
+//! ```rust
+//! # use clone_macro::clone;
+//! #[derive(Clone)]
+//! struct Draft {
+//!     lines: Vec<String>,
+//! }
+//!
+//! impl Draft {
+//!     fn signed(&self) -> impl FnOnce() -> Draft {
+//!         clone!([mut self as this], move || {
+//!             this.lines.push("-- Ferris".to_string());
+//!             this
+//!         })
+//!     }
+//! }
+//!
+//! let draft = Draft { lines: vec!["Hello".to_string()] };
+//!
+//! assert_eq!(draft.signed()().lines.len(), 2);
+//! assert_eq!(draft.lines.len(), 1);
+//! ```
+//!
+//! The clone is always of type `Self`, even when `self` is a reference.
+//! Capturing `self` without a new name fails to compile:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! # #[derive(Clone)]
+//! # struct Counter;
+//! impl Counter {
+//!     fn on_click(&self) -> impl Fn() {
+//!         clone!([self], move || {})
+//!     }
+//! }
+//! ```
+//!
+//! The macro can't pick a new name on its own either. Identifiers introduced by
+//! a macro are hygienic, so a `this` made up by `clone!` would be invisible to
+//! the closure body, which is written at the call site. For the same reason,
+//! `self` can't be combined with a prefix, as in `&self` or `weak self`:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! # use std::rc::Rc;
+//! # struct Counter;
+//! impl Counter {
+//!     fn on_click(self: Rc<Self>) -> impl Fn() {
+//!         clone!([weak self], move || {})
+//!     }
+//! }
+//! ```
+//!
+//! ## Typed Captures
+//! Any capture which binds an identifier can be annotated with a type, in which
+//! case the clone is converted into that type with [`Into`]. The annotation
+//! can optionally be prefixed with `into` to make the conversion stand out:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{path::{Path, PathBuf}, sync::Arc};
+//!
+//! let name = "Ferris".to_string();
+//! let path = PathBuf::from("/tmp");
+//!
+//! let c = clone!([name: Arc<str>, into path: Box<Path>], move || {
+//!     format!("{name} lives in {}", path.display())
+//! });
+//!
+//! assert_eq!(c(), "Ferris lives in /tmp");
+//! ```
+//!
+//! The list above desugars into:
+//! ```rust,ignore
+//! let name: Arc<str> = name.clone().into();
+//! let path: Box<Path> = path.clone().into();
+//! ```
+//!
+//! Expression captures can be annotated as well, as in
+//! `{ $expr } as ident: $ty`.
+//!
+//! ## Borrowed Captures
+//! Not every capture needs to be cloned. Prefixing an identifier with `&` or
+//! `&mut` captures a reference to it instead, which is handy for closures
+//! which don't need to be `'static`, such as the ones passed to
+//! [`std::thread::scope`]:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! let config = vec!["--verbose".to_string()];
+//! let counter = 0;
+//!
+//! std::thread::scope(|s| {
+//!     s.spawn(clone!([&config, mut counter], move || {
+//!         counter += config.len();
+//!
+//!         assert_eq!(counter, 1);
+//!     }));
+//! });
+//!
+//! assert_eq!(counter, 0);
+//! ```
+//!
+//! The list above desugars into:
+//! ```rust,ignore
+//! let config = &config;
+//! let mut counter = counter.clone();
+//! ```
+//!
+//! Field paths can be borrowed as well, in which case the reference is bound to
+//! a variable named after the last field, just like with
+//! [Field Captures](#field-captures), so `&self.config` is captured as `config`.
+//! Other prefixes can only be applied to identifiers.
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! struct Server {
+//!     routes: Vec<String>,
+//!     hits: usize,
+//! }
+//!
+//! let mut server = Server { routes: vec!["/".to_string()], hits: 0 };
+//!
+//! std::thread::scope(|s| {
+//!     s.spawn(clone!([&server.routes, &mut server.hits], move || {
+//!         *hits += routes.len();
+//!     }));
+//! });
+//!
+//! assert_eq!(server.hits, 1);
+//! ```
+//!
+//! ## Moved Captures
+//! Values which are no longer needed outside of the closure can be moved into
+//! it without being cloned by prefixing them with `move`, so that the capture
+//! list can still document everything the closure captures:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! let name = "Ferris".to_string();
+//! let greeting = "Hello".to_string();
+//!
+//! let c = clone!([name, move greeting], move || format!("{greeting}, {name}!"));
+//!
+//! assert_eq!(c(), "Hello, Ferris!");
+//!
+//! // `name` was cloned, so it can still be used here, but `greeting` was moved
+//! assert_eq!(name, "Ferris");
+//! ```
+//!
+//! `move mut ident` moves the value into a mutable binding.
+//!
+//! ## Reference Counted Captures
+//! A plain capture clones through [`Clone::clone`], which makes it impossible
+//! to tell whether a capture is a cheap reference count bump or a deep copy.
+//! Prefixing a capture with `rc` or `arc` clones it with [`Rc::clone`] or
+//! [`Arc::clone`] instead, which fails to compile if the capture isn't the
+//! matching pointer type, and plays nicely with clippy's `clone_on_ref_ptr`
+//! lint:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{rc::Rc, sync::Arc};
+//!
+//! let local = Rc::new(vec![1, 2, 3]);
+//! let shared = Arc::new("shared".to_string());
+//!
+//! let c = clone!([rc local, arc shared], move || local.len() + shared.len());
+//!
+//! assert_eq!(c(), 9);
+//! assert_eq!(Rc::strong_count(&local), 2);
+//! ```
+//!
+//! ```rust,compile_fail
+//! use clone_macro::clone;
+//!
+//! let not_an_rc = vec![1, 2, 3];
+//!
+//! clone!([rc not_an_rc], move || not_an_rc.len());
+//! ```
+//!
+//! [`Rc::clone`]: std::rc::Rc::clone
+//! [`Arc::clone`]: std::sync::Arc::clone
+//!
+//! ## Owned Captures
+//! Cloning a reference only copies the reference, so capturing a `&str` and
+//! sending it to another thread fails with a lifetime error. Prefixing the
+//! capture with `owned` converts the borrowed data into its owned counterpart
+//! with [`ToOwned`] instead, turning `&str` into `String`, `&[T]` into `Vec<T>`,
+//! and `&Path` into `PathBuf`:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! fn greet(name: &str) -> std::thread::JoinHandle<String> {
+//!     std::thread::spawn(clone!([owned name], move || format!("Hello, {name}!")))
+//! }
+//!
+//! assert_eq!(greet("Ferris").join().unwrap(), "Hello, Ferris!");
+//! ```
+//!
+//! ## Snapshot Captures
+//! Values behind a [`RefCell`](std::cell::RefCell), [`Mutex`](std::sync::Mutex)
+//! or [`RwLock`](std::sync::RwLock) can be captured as a point-in-time copy of
+//! the inner value by prefixing them with `borrow`, `lock` or `read`
+//! respectively. The guard is released as soon as the inner value has been
+//! cloned, and poisoned locks cause a panic.
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{
+//!     cell::RefCell,
+//!     rc::Rc,
+//!     sync::{Arc, Mutex, RwLock},
+//! };
+//!
+//! let config = Arc::new(Mutex::new(vec!["--verbose"]));
+//! let name = Rc::new(RefCell::new("Ferris".to_string()));
+//! let limit = Arc::new(RwLock::new(10));
+//!
+//! let c = clone!([lock config, borrow name, read limit], move || {
+//!     (config.len(), name.len(), limit)
+//! });
+//!
+//! config.lock().unwrap().push("--quiet");
+//! name.borrow_mut().clear();
+//! *limit.write().unwrap() = 20;
+//!
+//! // `c` still sees the values as they were when it was created
+//! assert_eq!(c(), (1, 6, 10));
+//! ```
+//!
+//! The list above desugars into:
+//! ```rust,ignore
+//! let config = config.lock().unwrap().clone();
+//! let name = name.borrow().clone();
+//! let limit = limit.read().unwrap().clone();
+//! ```
+//!
+//! ## Read Captures
+//! Some values are read rather than cloned. These can be captured with the
+//! following forms:
+//! - `get ident` reads a [`Cell`](std::cell::Cell) with [`Cell::get`](std::cell::Cell::get)
+//! - `load ident` loads an atomic with [`Ordering::SeqCst`](std::sync::atomic::Ordering::SeqCst),
+//!   while `load(Ordering) ident` uses the given ordering instead
+//! - `take ident` takes the value, leaving its default in its place, with
+//!   [`mem::take`](std::mem::take)
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{cell::Cell, sync::atomic::AtomicUsize};
+//!
+//! let flag = Cell::new(true);
+//! let counter = AtomicUsize::new(7);
+//! let mut events = vec!["click", "scroll"];
+//!
+//! let c = clone!([get flag, load(Relaxed) counter, take events], move || {
+//!     (flag, counter, events.len())
+//! });
+//!
+//! assert_eq!(c(), (true, 7, 2));
+//! assert!(events.is_empty());
+//! ```
+//!
+//! ## Fallible Captures
+//! Some values, such as files and sockets, can only be duplicated fallibly.
+//! Prefixing a capture with `try` duplicates it through [`TryClone`], and
+//! propagates any error from the enclosing function with `?`:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{fs::File, io};
+//!
+//! fn spawn_readers(file: File) -> io::Result<Vec<std::thread::JoinHandle<u64>>> {
+//!     let mut handles = vec![];
+//!
+//!     for _ in 0..4 {
+//!         handles.push(std::thread::spawn(clone!([try file], move || {
+//!             file.metadata().map(|metadata| metadata.len()).unwrap_or_default()
+//!         })));
+//!     }
+//!
+//!     Ok(handles)
+//! }
+//!
+//! for handle in spawn_readers(File::open("Cargo.toml")?)? {
+//!     assert!(handle.join().unwrap() > 0);
+//! }
+//! # Ok::<(), io::Error>(())
+//! ```
+//!
+//! ## Cheap Captures
+//! Prefixing the capture list with `cheap` only allows captures which are cheap
+//! to clone, as marked by the [`CheapClone`] trait. This is handy for hot
+//! callbacks, where accidentally deep cloning a large collection can go
+//! unnoticed for a long time:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let items = Rc::new(vec![1, 2, 3]);
+//! let multiplier = 2;
+//!
+//! let c = clone!(cheap [items, multiplier], move || {
+//!     items.iter().map(|item| item * multiplier).sum::<i32>()
+//! });
+//!
+//! assert_eq!(c(), 12);
+//! ```
+//!
+//! ```rust,compile_fail
+//! use clone_macro::clone;
+//!
+//! let items = vec![1, 2, 3];
+//!
+//! // `Vec` is not `CheapClone`
+//! clone!(cheap [items], move || items.len());
+//! ```
+//!
+//! For the same reason, `owned` captures, which always make a deep copy, are
+//! rejected in `cheap` capture lists:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! let bytes: &[u8] = &[1, 2, 3];
+//!
+//! clone!(cheap [owned bytes], move || bytes.len());
+//! ```
+//!
+//! So are `lazy` captures, which deep copy what they point to on the first
+//! call:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! # use std::rc::Rc;
+//! let big = Rc::new(vec![0u8; 1024]);
+//!
+//! clone!(cheap [lazy big], move || big.len());
+//! ```
+//!
+//! And so are typed captures, since the conversion can make a deep copy, as
+//! when turning a `&str` into a `String`:
+//! ```rust,compile_fail
+//! # use clone_macro::clone;
+//! let name = "Ferris";
+//!
+//! clone!(cheap [name: String], move || name.len());
+//! ```
+//!
+//! [`CheapClone`] can be implemented for your own handle types by hand, or, with
+//! the `derive` feature enabled, derived for types whose fields are all
+//! `CheapClone`:
+//! ```rust
+//! # #[cfg(feature = "derive")]
+//! # {
+//! use clone_macro::{clone, CheapClone};
+//! use std::rc::Rc;
+//!
+//! #[derive(Clone, CheapClone)]
+//! struct Handle {
+//!     name: Rc<str>,
+//! }
+//!
+//! let handle = Handle { name: "Ferris".into() };
+//!
+//! let c = clone!(cheap [handle], move || handle.name.len());
+//!
+//! assert_eq!(c(), 6);
+//! # }
+//! ```
+//!
+//! ## Per-Call Clones
+//! Closures which spawn or return a future every time they are called need to
+//! clone their captures once more for every future, since each future takes
+//! ownership of its own copy. Prefixing the capture list with `each` does
+//! exactly that, cloning the captures once into the closure, and then once
+//! again every time the closure is called:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let name = Rc::new("Ferris".to_string());
+//! let mut tasks = Vec::new();
+//!
+//! let on_click = clone!(each [name], move || {
+//!     async move {
+//!         println!("{name} clicked!");
+//!     }
+//! });
+//!
+//! // `name` was cloned once into the closure
+//! assert_eq!(Rc::strong_count(&name), 2);
+//!
+//! tasks.push(on_click());
+//! tasks.push(on_click());
+//!
+//! // and once more for every future
+//! assert_eq!(Rc::strong_count(&name), 4);
+//! ```
+//!
+//! The above desugars into:
+//! ```rust
+//! # let name = "Ferris".to_string();
+//! {
+//!     let name = name.clone();
+//!
+//!     move || {
+//!         let name = name.clone();
+//!
+//!         async move {
+//!             println!("{name} clicked!");
+//!         }
+//!     }
+//! };
+//! ```
+//!
+//! Every capture is cloned again, including ones which weren't cloned in the
+//! first place, such as `move` and `&` captures. Weak captures are upgraded on
+//! every call as usual. Lazy captures still clone their pointee only on the
+//! first call, but then clone that copy once more on every call, so that the
+//! future owns it rather than borrowing it from the closure:
+//! ```rust
+//! # use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let words = Rc::new(vec!["lazy", "each"]);
+//!
+//! let count = clone!(each [lazy words], move || async move { words.len() });
+//!
+//! let futures = [count(), count()];
+//!
+//! // The futures own their copies, so they outlive the closure
+//! drop(count);
+//! # drop(futures);
+//! ```
 //!
-//! For example, the following is a valid call
-//! ```rust
+//! `&mut`, `take` and `try` captures can't be produced more than once, and
+//! neither can destructuring patterns, so they fail to compile in `each`
+//! lists:
+//! ```rust,compile_fail
 //! # use clone_macro::clone;
+//! let mut log = Vec::<String>::new();
 //!
-//! let a = 1;
-//! let b = 2;
+//! clone!(each [&mut log], move || log.push("clicked".to_string()));
+//! ```
 //!
-//! clone!([mut a, b], ());
+//! Modes can be combined, so `cheap each [...]` only allows cheap captures
+//! which are cloned on every call.
+//!
+//! ## Boxed Closures
+//! Closures which are stored in a field, such as a list of callbacks, usually
+//! need to be boxed into a trait object first. Putting `box`, `rc` or `arc`
+//! followed by the trait object type right before the capture list wraps the
+//! result in a [`Box`], [`Rc`](std::rc::Rc) or [`Arc`](std::sync::Arc) of that
+//! type:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! struct Button {
+//!     on_click: Vec<Box<dyn Fn(u32) -> String + Send>>,
+//! }
+//!
+//! let name = "Ferris".to_string();
+//! let mut button = Button { on_click: Vec::new() };
+//!
+//! button.on_click.push(clone!(box dyn Fn(u32) -> String + Send [name], |clicks| {
+//!     format!("{name} clicked {clicks} times")
+//! }));
+//!
+//! assert_eq!((button.on_click[0])(3), "Ferris clicked 3 times");
 //! ```
 //!
-//! and desugars down to:
+//! The pointer type has to come after any other modes, right before the
+//! capture list, as in `cheap box dyn Fn() [...]`.
+//!
+//! ## Boxed Futures
+//! Similarly, futures which are stored or sent to a job queue usually need to
+//! be pinned and boxed. Putting `future` before the capture list turns the
+//! result into a `Pin<Box<dyn Future<Output = _> + 'static>>`, and
+//! `future + Send` into a `Pin<Box<dyn Future<Output = _> + Send + 'static>>`:
 //! ```rust
-//! let a = 1;
-//! let b = 2;
+//! use clone_macro::clone;
+//! use std::{future::Future, pin::Pin};
 //!
-//! {
-//!     let mut a = a.clone();
-//!     let b = b.clone();
+//! type Job = Pin<Box<dyn Future<Output = usize> + Send>>;
 //!
-//!     ()
-//! };
+//! let name = "Ferris".to_string();
+//! let mut jobs: Vec<Job> = Vec::new();
+//!
+//! jobs.push(clone!(future + Send [name], async { name.len() }));
 //! ```
 //!
-//! The clone list can also take a second form, which is an arbitrary expression
-//! followed by `as` and the name of the variable. For example:
+//! ## Asserting Bounds
+//! Closures which are sent to another thread have to be `Send` and `'static`,
+//! but when a capture isn't, the error only shows up wherever the closure ends
+//! up being used, such as inside [`std::thread::spawn`]. Prefixing the capture
+//! list with `send`, `sync` and/or `static` checks every capture, as well as
+//! the resulting value, against the corresponding bound, so that the error is
+//! reported at the `clone!` call instead:
 //! ```rust
+//! use clone_macro::clone;
+//! use std::sync::Arc;
+//!
+//! let name = Arc::new("Ferris".to_string());
+//!
+//! let greet = clone!(send static [name], || format!("Hello, {name}!"));
+//!
+//! std::thread::spawn(greet).join().unwrap();
+//! ```
+//!
+//! Capturing an [`Rc`](std::rc::Rc) fails to compile right away, with an
+//! error which names the capture, as in "within `Capture<name, Rc<String>>`,
+//! the trait `Send` is not implemented for `Rc<String>`":
+//! ```rust,compile_fail
 //! # use clone_macro::clone;
+//! use std::rc::Rc;
 //!
-//! let s = "Hello, there!";
+//! let name = Rc::new("Ferris".to_string());
 //!
-//! clone!([{ s.len() } as len], move || {
-//!     assert_eq!(len, "Hello, there!".len());
-//! });
+//! let greet = clone!(send [name], || format!("Hello, {name}!"));
 //! ```
 //!
-//! The above desugars into:
+//! These words, like `cheap`, `each` and the other modes, are only treated as
+//! modes when a capture list follows them, so variables with the same names
+//! can still be cloned without one:
 //! ```rust
 //! # use clone_macro::clone;
+//! use std::rc::Rc;
 //!
-//! let s = "Hello, there!";
+//! let send = "Ferris".to_string();
+//! let s = Rc::new(1);
+//!
+//! clone!(send, s);
+//!
+//! assert_eq!(send, "Ferris");
+//! assert_eq!(Rc::strong_count(&s), 2);
+//! ```
+//!
+//! ## Weak Captures
+//! Capturing an [`Rc`](std::rc::Rc) or [`Arc`](std::sync::Arc) with a strong
+//! clone keeps its owner alive for as long as the closure lives, which makes
+//! it very easy to leak reference cycles. Prefixing a capture with `weak`
+//! stores a weak reference instead, which is upgraded every time the closure
+//! runs. If the owner has already been dropped, the closure returns early
+//! without running its body.
+//! ```rust
+//! use clone_macro::clone;
+//! use std::{cell::Cell, rc::Rc};
+//!
+//! let counter = Rc::new(Cell::new(0));
+//!
+//! let increment = clone!([weak counter], move || {
+//!     counter.set(counter.get() + 1);
+//! });
 //!
+//! increment();
+//!
+//! assert_eq!(counter.get(), 1);
+//!
+//! drop(counter);
+//!
+//! // Does nothing, since `counter` no longer exists
+//! increment();
+//! ```
+//!
+//! The above desugars into:
+//! ```rust
+//! # use std::{cell::Cell, rc::Rc};
+//! # let counter = Rc::new(Cell::new(0));
 //! {
-//!     let len = "Hello, there!".len();
+//!     let counter = Rc::downgrade(&counter);
 //!
 //!     move || {
-//!         assert_eq!(len, "Hello, there!".len());
+//!         let counter = match counter.upgrade() {
+//!             Some(counter) => counter,
+//!             None => return,
+//!         };
+//!
+//!         counter.set(counter.get() + 1);
 //!     }
 //! };
 //! ```
 //!
-//! This macro is most useful when the second argument is a closure, and is what
-//! it is intended to work with, though not strictly so.
+//! Weak captures work with any type implementing [`Downgrade`], and can only
+//! be used when the body is a closure or an `async` block.
 //!
-//! All forms mentioned above can be mixed and matched, including adding a `mut` modifier
-//! for the second form as:
-//! ```rust,ignore
-//! mut { $expr } as $ident
+//! Since `weak counter` isn't a valid expression, rustfmt leaves the whole
+//! `clone!` call, including its body, unformatted when it contains a weak
+//! capture.
+//!
+//! ### Fallbacks
+//! By default, the closure returns `()` when a weak capture cannot be upgraded.
+//! Closures which return something else can specify what should be returned
+//! instead with the `@default-return` directive, or panic with
+//! `@default-panic`, which optionally takes a message:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let handler = Rc::new(|event: &str| event == "click");
+//!
+//! let on_event = clone!([weak handler, @default-return false], move |event| {
+//!     handler(event)
+//! });
+//!
+//! assert!(on_event("click"));
+//!
+//! drop(handler);
+//!
+//! assert!(!on_event("click"));
+//! ```
+//!
+//! ```rust,should_panic
+//! use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let window = Rc::new("main");
+//!
+//! let title = clone!([@default-panic "window was closed", weak window], move || {
+//!     window.to_string()
+//! });
+//!
+//! drop(window);
+//!
+//! title();
+//! ```
+//!
+//! Directives can appear anywhere in the capture list, and if more than one
+//! is given, the last one wins.
+//!
+//! Like weak captures, directives aren't valid expressions, so rustfmt doesn't
+//! format `clone!` calls which contain them.
+//!
+//! ## Lazy Captures
+//! Callbacks are often registered but never called, in which case eagerly
+//! cloning a large value is wasted work. Prefixing a capture which is behind a
+//! pointer, such as an [`Rc`](std::rc::Rc) or [`Arc`](std::sync::Arc), with
+//! `lazy` only clones the pointer up front. The pointee itself is cloned the
+//! first time the closure runs, and the clone is reused from then on. Inside
+//! the closure, the capture is a reference to the cloned value:
+//! ```rust
+//! use clone_macro::clone;
+//! use std::rc::Rc;
+//!
+//! let words = Rc::new(vec!["lazy".to_string(), "capture".to_string()]);
+//!
+//! let c = clone!([lazy words], move || words.join(" "));
+//!
+//! assert_eq!(c(), "lazy capture");
+//! ```
+//!
+//! See [`Lazy`] for details.
+//!
+//! ## Attributes
+//! Any capture can be preceded by outer attributes, which are applied to the
+//! bindings `clone!` generates for it. This makes it possible to only capture
+//! something when a feature is enabled, or to silence lints on a single
+//! capture:
+//! ```rust
+//! use clone_macro::clone;
+//!
+//! let name = "Ferris".to_string();
+//! let buf = Vec::<u8>::new();
+//!
+//! let c = clone!([
+//!     #[cfg(debug_assertions)] name,
+//!     #[allow(unused_mut)] mut buf,
+//! ], move || {
+//!     #[cfg(debug_assertions)]
+//!     println!("{name}");
+//!
+//!     buf.len()
+//! });
+//!
+//! assert_eq!(c(), 0);
 //! ```
 //!
 //! # Examples
@@ -152,38 +1036,651 @@
 //! assert_eq!(s.some_field.as_str(), "Beyond measure.");
 //! ```
 
+mod cheap;
+mod lazy;
+mod try_clone;
+mod weak;
+
+#[doc(hidden)]
+#[path = "private.rs"]
+pub mod __private;
+
+pub use cheap::CheapClone;
+#[cfg(feature = "derive")]
+pub use clone_macro_derive::CheapClone;
+pub use lazy::Lazy;
+pub use try_clone::TryClone;
+pub use weak::{Downgrade, Upgrade};
+
 /// Please see the crate documentation for syntax and examples, but in a jist, the
 /// syntax is as follows:
 /// ```ignore
-/// clone!([$($(mut)? $FORM)*], $expr);
+/// clone!($($MODE)* [$($(mut)? $FORM)*], $expr);
 /// ```
 ///
-/// where `$FORM` is one of either:
+/// where `$MODE` is one of either:
+/// - `cheap`
+/// - `each`
+/// - `box $ty`, `rc $ty` or `arc $ty`, which must come right before the list
+/// - `send`, `sync` or `static`
+/// - `future` or `future + Send`, which must come right before the list
+///
+/// and `$FORM` is one of either:
 /// - `ident`
-/// - `{ $expr } as ident`
+/// - `{ $expr } as ident` or `{ $expr } as $pat`, where the braces are optional
+///   unless `$expr` contains an `as` cast or a comma outside of any brackets
+/// - `ident: $ty` or `{ $expr } as ident: $ty`
+/// - `ident.field...`
+/// - `ident as ident` or `self as ident`
+/// - `weak ident`
+/// - `&ident` or `&mut ident`, where `ident` may be followed by a field path
+/// - `move ident` or `move mut ident`
+/// - `rc ident` or `arc ident`
+/// - `owned ident`
+/// - `borrow ident`, `lock ident` or `read ident`
+/// - `get ident`, `load$(($ordering))? ident` or `take ident`
+/// - `try ident`
+/// - `lazy ident`
+///
+/// and the list may also contain one of the following directives:
+/// - `@default-return $expr`
+/// - `@default-panic $($msg)?`
+///
+/// Each `$FORM` may be preceded by any number of outer attributes, such as
+/// `#[cfg(..)]` or `#[allow(..)]`.
+///
+/// Capture lists are expanded without recursing once per capture, so they
+/// aren't limited by `#![recursion_limit]`, as long as every expression
+/// without braces is an identifier or a field path, optionally dereferenced
+/// with `*` or followed by a call such as `.len()` or an index such as `[0]`.
+/// Lists with a capture the non-recursive matcher can't take apart, such as
+/// `a + b as sum` or `Config::default() as config`, are expanded recursively
+/// instead, which needs a higher `#![recursion_limit]` for lists of more than
+/// about a hundred captures.
 #[macro_export]
 macro_rules! clone {
     () => {};
-    ([$($tt:tt)*], $expr:expr) => {{
-        clone!($($tt)*);
+
+    // Modes preceding the capture list. The leading group holds the fallback
+    // used when an `@inner` capture cannot be produced, the path of the
+    // function used to clone captures, whether captures are cloned again
+    // every time the closure runs, and the bounds each capture is checked
+    // against. The group after it holds the whole invocation, so that words
+    // which turn out not to be followed by a capture list, as in
+    // `clone!(send, s)`, can be captured as plain identifiers instead.
+    (@mode $cfg:tt $orig:tt [$($tt:tt)*], $($body:tt)+) => {
+        $crate::clone!(@list $cfg [$($tt)*] $($body)+)
+    };
+    // The annotated `let` is what unsizes the pointer into the requested
+    // trait object.
+    (@mode $cfg:tt $orig:tt box $ty:ty [$($tt:tt)*], $($body:tt)+) => {{
+        let callback: ::std::boxed::Box<$ty> = ::std::boxed::Box::new($crate::clone!(@list $cfg [$($tt)*] $($body)+));
+        callback
+    }};
+    (@mode $cfg:tt $orig:tt rc $ty:ty [$($tt:tt)*], $($body:tt)+) => {{
+        let callback: ::std::rc::Rc<$ty> = ::std::rc::Rc::new($crate::clone!(@list $cfg [$($tt)*] $($body)+));
+        callback
+    }};
+    (@mode $cfg:tt $orig:tt arc $ty:ty [$($tt:tt)*], $($body:tt)+) => {{
+        let callback: ::std::sync::Arc<$ty> = ::std::sync::Arc::new($crate::clone!(@list $cfg [$($tt)*] $($body)+));
+        callback
+    }};
+    // A helper function is needed here, since the output type of the future
+    // can't be named in a `let` annotation.
+    (@mode $cfg:tt $orig:tt future + Send [$($tt:tt)*], $($body:tt)+) => {
+        $crate::__private::pin_send_future($crate::clone!(@list $cfg [$($tt)*] $($body)+))
+    };
+    (@mode $cfg:tt $orig:tt future [$($tt:tt)*], $($body:tt)+) => {
+        $crate::__private::pin_future($crate::clone!(@list $cfg [$($tt)*] $($body)+))
+    };
+    (@mode [$fallback:tt $clone:tt $each:tt $bounds:tt] $orig:tt cheap $($tt:tt)+) => {
+        $crate::clone!(@mode [$fallback [$crate::CheapClone::cheap_clone] $each $bounds] $orig $($tt)+)
+    };
+    (@mode [$fallback:tt $clone:tt $each:tt $bounds:tt] $orig:tt each $($tt:tt)+) => {
+        $crate::clone!(@mode [$fallback $clone [each] $bounds] $orig $($tt)+)
+    };
+    (@mode [$fallback:tt $clone:tt $each:tt [$($bound:ident)*]] $orig:tt send $($tt:tt)+) => {
+        $crate::clone!(@mode [$fallback $clone $each [$($bound)* send]] $orig $($tt)+)
+    };
+    (@mode [$fallback:tt $clone:tt $each:tt [$($bound:ident)*]] $orig:tt sync $($tt:tt)+) => {
+        $crate::clone!(@mode [$fallback $clone $each [$($bound)* sync]] $orig $($tt)+)
+    };
+    (@mode [$fallback:tt $clone:tt $each:tt [$($bound:ident)*]] $orig:tt static $($tt:tt)+) => {
+        $crate::clone!(@mode [$fallback $clone $each [$($bound)* static]] $orig $($tt)+)
+    };
+    (@mode $cfg:tt [$($orig:tt)*] $($tt:tt)*) => {
+        $crate::clone!(@list [{ return } [::core::clone::Clone::clone] [] []] [$($orig)*])
+    };
+
+    // Lists whose items are made up of nothing but words, with an optional
+    // `&` or `*` in front, any number of braced groups each followed by more
+    // words, a field path, which may use tuple indices, a parenthesized group,
+    // a bracketed group and a type, which covers most forms, are matched in
+    // one go rather than munched one item at a time, so the expansion depth
+    // doesn't grow with the length of the list. Braced groups cover both
+    // `{ $expr } as ..` and struct patterns after `as`. Directives are taken
+    // out first, since they apply to the whole list. Each item is kept as an
+    // unparsed `words` descriptor, which `@outer` parses through `@capture` on
+    // its own. A trailing comma leaves an empty `words` descriptor, which
+    // expands to nothing. A single item of any other form sends the whole list
+    // through the recursive `@capture` path instead.
+    (@list $cfg:tt [] $($body:tt)*) => {
+        $crate::clone!(@build $cfg [] $($body)*)
+    };
+    (@list [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($(#[$attr:meta])* $(& $amp:ident)? $(* $deref:ident)? $($word:ident)* $({ $($braced:tt)* } $($post:ident)*)* $(. $field:tt $($more:ident)*)* $(( $($group:tt)* ) $($tail:ident)*)? $([ $($index:tt)* ] $($after:ident)*)? $(: $ty:ty)? ,)* @default-return $value:expr $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@list [{ return $value } $clone $each $bounds] [$($(#[$attr])* $(& $amp)? $(* $deref)? $($word)* $({ $($braced)* } $($post)*)* $(. $field $($more)*)* $(( $($group)* ) $($tail)*)? $([ $($index)* ] $($after)*)? $(: $ty)? ,)* $($($tt)*)?] $($body)*)
+    };
+    (@list [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($(#[$attr:meta])* $(& $amp:ident)? $(* $deref:ident)? $($word:ident)* $({ $($braced:tt)* } $($post:ident)*)* $(. $field:tt $($more:ident)*)* $(( $($group:tt)* ) $($tail:ident)*)? $([ $($index:tt)* ] $($after:ident)*)? $(: $ty:ty)? ,)* @default-panic $msg:literal $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@list [{ ::core::panic!($msg) } $clone $each $bounds] [$($(#[$attr])* $(& $amp)? $(* $deref)? $($word)* $({ $($braced)* } $($post)*)* $(. $field $($more)*)* $(( $($group)* ) $($tail)*)? $([ $($index)* ] $($after)*)? $(: $ty)? ,)* $($($tt)*)?] $($body)*)
+    };
+    (@list [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($(#[$attr:meta])* $(& $amp:ident)? $(* $deref:ident)? $($word:ident)* $({ $($braced:tt)* } $($post:ident)*)* $(. $field:tt $($more:ident)*)* $(( $($group:tt)* ) $($tail:ident)*)? $([ $($index:tt)* ] $($after:ident)*)? $(: $ty:ty)? ,)* @default-panic $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@list [{ ::core::panic!("failed to upgrade `weak` capture") } $clone $each $bounds] [$($(#[$attr])* $(& $amp)? $(* $deref)? $($word)* $({ $($braced)* } $($post)*)* $(. $field $($more)*)* $(( $($group)* ) $($tail)*)? $([ $($index)* ] $($after)*)? $(: $ty)? ,)* $($($tt)*)?] $($body)*)
+    };
+    (@list $cfg:tt [$($(#[$attr:meta])* $(& $amp:ident)? $(* $deref:ident)? $($word:ident)* $({ $($braced:tt)* } $($post:ident)*)* $(. $field:tt $($more:ident)*)* $(( $($group:tt)* ) $($tail:ident)*)? $([ $($index:tt)* ] $($after:ident)*)? $(: $ty:ty)?),*] $($body:tt)*) => {
+        $crate::clone!(@build $cfg [$((words $(#[$attr])* $(& $amp)? $(* $deref)? $($word)* $({ $($braced)* } $($post)*)* $(. $field $($more)*)* $(( $($group)* ) $($tail)*)? $([ $($index)* ] $($after)*)? $(: $ty)?))*] $($body)*)
+    };
+    (@list $cfg:tt [$($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [] [$($tt)*] $($body)*)
+    };
+
+    // Every item in the capture list is turned into a descriptor, which is
+    // expanded by `@outer` before the body, and by `@inner` at the start of
+    // the closure body every time it runs.
+    (@capture [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? @default-return $value:expr $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [{ return $value } $clone $each $bounds] [$($desc)*] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? @default-panic $msg:literal $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [{ ::core::panic!($msg) } $clone $each $bounds] [$($desc)*] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt $clone:tt $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? @default-panic $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [{ ::core::panic!("failed to upgrade `weak` capture") } $clone $each $bounds] [$($desc)*] [$($($tt)*)?] $($body)*)
+    };
+    // `self` can only be captured under a new name, so every other form which
+    // would bind it, with or without a prefix, is rejected here, before the
+    // typed and prefixed arms get to emit `let self = ...`. Each of them ends
+    // at the `self`, so that `mut self as this` still reaches `@rename`.
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $(mut)? self $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!("`self` cannot be rebound, so it must be given a new name, as in `self as this`")
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $(mut)? self: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!("`self` cannot be rebound, so it must be given a new name, as in `{ self } as this: T`")
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* & $(mut)? self $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!("`self` cannot be rebound, so it can't be captured by reference, use `self` directly in the body instead")
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* load($ordering:ident) self $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!("`self` cannot be rebound, so `load(..)` can't be applied to it")
+    };
+    // `mut` would otherwise be taken for a prefix of `self` below.
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut $source:ident as $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@rename $cfg [$($desc)*] [$(#[$attr])*] [mut] $source $source $ident [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* mut $source:ident as $($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs [$(#[$attr])*] [mut] [$source] [as $($tt)+] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $prefix:ident $(mut)? self $(as $($rest:tt)*)? $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`self` cannot be rebound, so `",
+            ::core::stringify!($prefix),
+            "` can't be applied to it",
+        ))
+    };
+    // `Into` can make a deep copy of the clone, so like `owned`, typed
+    // captures are only allowed when captures are cloned through `Clone`.
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut { $expr:expr } as $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (into [$(#[$attr])*] [mut] $ident [$ty] [$expr])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* { $expr:expr } as $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (into [$(#[$attr])*] [] $ident [$ty] [$expr])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (into [$(#[$attr])*] [mut] $ident [$ty] [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* into $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (into [$(#[$attr])*] [] $ident [$ty] [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (into [$(#[$attr])*] [] $ident [$ty] [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $(mut)? { $expr:expr } as $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg $descs [$ident: $ty])
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* mut $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg $descs [$ident: $ty])
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* into $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg $descs [$ident: $ty])
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $ident:ident: $ty:ty $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($ident),
+            ": ",
+            ::core::stringify!($ty),
+            "` converts the clone with `Into`, which can make a deep copy, so it can't be used in a `cheap` capture list",
+        ))
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut $root:ident . $field:ident $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@field $cfg [$($desc)*] [$(#[$attr])*] [mut] [$root] $field [$($tt)*] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* $root:ident . $field:ident $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@field $cfg [$($desc)*] [$(#[$attr])*] [] [$root] $field [$($tt)*] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* $source:ident as $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@rename $cfg [$($desc)*] [$(#[$attr])*] [] $source $source $ident [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut { $expr:expr } as $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [mut] $ident [$expr])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* mut { $expr:expr } as $pat:pat $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($pat),
+            "` is a destructuring pattern, so `mut` has to go on the bindings inside it",
+        ))
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* mut $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [mut] $ident [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* &mut $root:ident . $field:ident $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@field $cfg [$($desc)*] [$(#[$attr])*] [&mut] [$root] $field [$($tt)*] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* & $root:ident . $field:ident $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@field $cfg [$($desc)*] [$(#[$attr])*] [&] [$root] $field [$($tt)*] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* &mut $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (once [$(#[$attr])*] [] $ident [&mut $ident] ["&mut "])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* & $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [&$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* move mut $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [mut] $ident [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* move $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* rc $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [::std::rc::Rc::clone(&$ident)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* arc $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [::std::sync::Arc::clone(&$ident)])] [$($($tt)*)?] $($body)*)
+    };
+    // `owned` always makes a deep copy, so it is only allowed when captures
+    // are cloned through `Clone`, and not in `cheap` lists.
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* owned $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (let [$(#[$attr])*] [] $ident [::std::borrow::ToOwned::to_owned(&*$ident)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* owned $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`owned ",
+            ::core::stringify!($ident),
+            "` always makes a deep copy, so it can't be used in a `cheap` capture list",
+        ))
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* borrow $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [] $ident [*::core::cell::RefCell::borrow(&$ident)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* lock $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [] $ident [*::std::sync::Mutex::lock(&$ident).unwrap()])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* read $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [] $ident [*::std::sync::RwLock::read(&$ident).unwrap()])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* get $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [::core::cell::Cell::get(&$ident)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* load($ordering:ident) $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [$ident.load(::core::sync::atomic::Ordering::$ordering)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* load $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let [$(#[$attr])*] [] $ident [$ident.load(::core::sync::atomic::Ordering::SeqCst)])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* take $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (once [$(#[$attr])*] [] $ident [::core::mem::take(&mut $ident)] ["take "])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* try $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (once [$(#[$attr])*] [] $ident [$crate::TryClone::try_clone(&$ident)?] ["try "])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* weak $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (weak [$(#[$attr])*] $ident)] [$($($tt)*)?] $($body)*)
+    };
+    // Like `owned`, `lazy` deep copies the pointee on the first call, so it
+    // is rejected in `cheap` lists too.
+    (@capture [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] [$($desc:tt)*] [$(,)? $(#[$attr:meta])* lazy $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture [$fallback [::core::clone::Clone::clone] $each $bounds] [$($desc)* (lazy [$(#[$attr])*] $ident)] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* lazy $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`lazy ",
+            ::core::stringify!($ident),
+            "` deep copies what it points to on the first call, so it can't be used in a `cheap` capture list",
+        ))
+    };
+    // Apart from `&` and `&mut`, prefixes only apply to identifiers.
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $prefix:ident $(($($arg:tt)*))? $($word:ident)+ . $field:ident $($tt:tt)*] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($prefix $(($($arg)*))? $($word)+ . $field),
+            "` is a field path, but `",
+            ::core::stringify!($prefix),
+            "` can only be applied to an identifier",
+        ))
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* { $expr:expr } as $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [] $ident [$expr])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* { $expr:expr } as $pat:pat $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (pat [$(#[$attr])*] [$pat] [$expr])] [$($($tt)*)?] $($body)*)
+    };
+    // Every valid braced capture has been matched by now, and handing the rest
+    // to `@expr` would only have it handed back here again.
+    (@capture $cfg:tt $descs:tt [$(,)? $(#[$attr:meta])* $(mut)? { $($expr:tt)* } as $($tt:tt)+] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "expected a single name or pattern followed by `,` in `",
+            ::core::stringify!({ $($expr)* } as $($tt)+),
+            "`",
+        ))
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)? $(#[$attr:meta])* $ident:ident $(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone [$(#[$attr])*] [] $ident [$ident])] [$($($tt)*)?] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [, $($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs [] [] [] [$($tt)+] $($body)*)
+    };
+    (@capture $cfg:tt [$desc:tt] [$(,)?] @then [$($then:tt)*]) => {
+        $crate::clone!($($then)* $desc)
+    };
+    (@capture $cfg:tt [$($desc:tt)*] [$(,)?] $($body:tt)*) => {
+        $crate::clone!(@build $cfg [$($desc)*] $($body)*)
+    };
+    (@capture $cfg:tt $descs:tt [$($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs [] [] [] [$($tt)+] $($body)*)
+    };
+
+    // `self` is usually a reference, so the type of its clone is pinned to
+    // `Self` to avoid cloning the reference instead. The source is passed in
+    // twice, since matching it against `self` would otherwise lose the
+    // caller's `self` token.
+    (@rename $cfg:tt [$($desc:tt)*] $attrs:tt $mut:tt self $source:ident $ident:ident [$($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone $attrs $mut $ident [$source] [Self])] [$($tt)*] $($body)*)
+    };
+    (@rename $cfg:tt [$($desc:tt)*] $attrs:tt $mut:tt $source:ident $_source:ident $ident:ident [$($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone $attrs $mut $ident [$source])] [$($tt)*] $($body)*)
+    };
+
+    // Field paths are bound to a variable named after their last segment.
+    // Borrowed field paths are marked with `[&]` or `[&mut]` in place of
+    // `mut`.
+    (@field $cfg:tt $descs:tt $attrs:tt $mut:tt [$($path:tt)*] $field:ident [. $next:ident $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@field $cfg $descs $attrs $mut [$($path)* . $field] $next [$($tt)*] $($body)*)
+    };
+    (@field $cfg:tt [$($desc:tt)*] $attrs:tt [&] [$($path:tt)*] $field:ident [$(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (let $attrs [] $field [&$($path)* . $field])] [$($($tt)*)?] $($body)*)
+    };
+    (@field $cfg:tt [$($desc:tt)*] $attrs:tt [&mut] [$($path:tt)*] $field:ident [$(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (once $attrs [] $field [&mut $($path)* . $field] ["&mut "])] [$($($tt)*)?] $($body)*)
+    };
+    (@field $cfg:tt $descs:tt $attrs:tt [& $($mut:tt)?] [$($path:tt)*] $field:ident [$($tt:tt)+] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "expected `,` after `&",
+            ::core::stringify!($($mut)? $($path)* . $field),
+            "`",
+        ))
+    };
+    (@field $cfg:tt [$($desc:tt)*] $attrs:tt $mut:tt [$($path:tt)*] $field:ident [$(, $($tt:tt)*)?] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg [$($desc)* (clone $attrs $mut $field [$($path)* . $field])] [$($($tt)*)?] $($body)*)
+    };
+    (@field $cfg:tt $descs:tt $attrs:tt $mut:tt [$($path:tt)*] $field:ident [$($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs $attrs $mut [$($path)* . $field] [$($tt)+] $($body)*)
+    };
+
+    // Expressions without braces are collected up to the first `as`, and then
+    // handled as if they had been wrapped in braces.
+    (@expr $cfg:tt $descs:tt [$($attr:tt)*] $mut:tt [] [#[$next:meta] $($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs [$($attr)* #[$next]] $mut [] [$($tt)+] $($body)*)
+    };
+    (@expr $cfg:tt $descs:tt $attrs:tt [] [] [mut $($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs $attrs [mut] [] [$($tt)+] $($body)*)
+    };
+    (@expr $cfg:tt $descs:tt [$($attr:tt)*] [$($mut:tt)?] [$($expr:tt)+] [as $($tt:tt)+] $($body:tt)*) => {
+        $crate::clone!(@capture $cfg $descs [$($attr)* $($mut)? { $($expr)+ } as $($tt)+] $($body)*)
+    };
+    (@expr $cfg:tt $descs:tt $attrs:tt $mut:tt [$($expr:tt)*] [$(, $($tt:tt)*)?] $($body:tt)*) => {
+        ::core::compile_error!(::core::concat!(
+            "expected `as` after `",
+            ::core::stringify!($($expr)*),
+            "`",
+        ))
+    };
+    (@expr $cfg:tt $descs:tt $attrs:tt $mut:tt [$($expr:tt)*] [$next:tt $($tt:tt)*] $($body:tt)*) => {
+        $crate::clone!(@expr $cfg $descs $attrs $mut [$($expr)* $next] [$($tt)*] $($body)*)
+    };
+
+    // `words` descriptors are parsed into an actual descriptor on their own,
+    // which is then handed back to `@outer`. This is the only place where
+    // every one of them is parsed, since `@inner` and `@detached` only need
+    // to pick out a few forms. Each capture is bound and then checked against
+    // the requested bounds.
+    (@outer $cfg:tt (words)) => {};
+    (@outer $cfg:tt (words $($item:tt)+)) => {
+        $crate::clone!(@capture $cfg [] [$($item)+] @then [@outer $cfg])
+    };
+    (@outer $cfg:tt $desc:tt) => {
+        $crate::clone!(@bind $cfg $desc);
+        $crate::clone!(@assert $cfg $desc);
+    };
+
+    (@bind [$fallback:tt [$($clone:tt)*] $each:tt $bounds:tt] (clone [$($attr:tt)*] $mut:tt $ident:ident [$src:expr] $([$ty:ty])?)) => {
+        $($attr)* let $crate::clone!(@binding $each $mut $ident) $(: $ty)? = $($clone)*(&$src);
+    };
+    (@bind [$fallback:tt [$($clone:tt)*] $each:tt $bounds:tt] (into [$($attr:tt)*] $mut:tt $ident:ident [$ty:ty] [$src:expr])) => {
+        $($attr)* let $crate::clone!(@binding $each $mut $ident): $ty = ::core::convert::Into::into($($clone)*(&$src));
+    };
+    (@bind [$fallback:tt $clone:tt $each:tt $bounds:tt] (let [$($attr:tt)*] $mut:tt $ident:ident [$($init:tt)*])) => {
+        $($attr)* let $crate::clone!(@binding $each $mut $ident) = $($init)*;
+    };
+    (@bind $cfg:tt (once $attrs:tt $mut:tt $ident:ident $init:tt $form:tt)) => {
+        $crate::clone!(@bind $cfg (let $attrs $mut $ident $init))
+    };
+    // Patterns don't bind a single identifier which `@assert` could check, so
+    // the value is checked before it is destructured instead.
+    (@bind [$fallback:tt [$($clone:tt)*] $each:tt [$($bound:ident)*]] (pat [$($attr:tt)*] [$pat:pat] [$src:expr])) => {
+        $($attr)* let $pat = {
+            let value = $($clone)*(&$src);
+            $($crate::clone!(@bound $bound [] value);)*
+            value
+        };
+    };
+    (@bind $cfg:tt (weak [$($attr:tt)*] $ident:ident)) => {
+        $($attr)* let $ident = $crate::Downgrade::downgrade(&$ident);
+    };
+    (@bind [$fallback:tt [$($clone:tt)*] $each:tt $bounds:tt] (lazy [$($attr:tt)*] $ident:ident)) => {
+        $($attr)* let $ident = $crate::Lazy::new($($clone)*(&$ident));
+    };
+
+    // Only `weak` and `lazy` captures need anything done in the closure,
+    // unless every capture is cloned again on every call.
+    (@inner [$fallback:tt $clone:tt [each] $bounds:tt] (words $($item:tt)+)) => {
+        $crate::clone!(@capture [$fallback $clone [each] $bounds] [] [$($item)+] @then [@inner [$fallback $clone [each] $bounds]])
+    };
+    (@inner $cfg:tt (words $(#[$attr:meta])* $prefix:ident self)) => {};
+    (@inner $cfg:tt (words $(#[$attr:meta])* weak $ident:ident)) => {
+        $crate::clone!(@inner $cfg (weak [$(#[$attr])*] $ident))
+    };
+    // `lazy` captures in `cheap` lists have already been rejected by
+    // `@capture`, so they are left alone here.
+    (@inner [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] (words $(#[$attr:meta])* lazy $ident:ident)) => {
+        $crate::clone!(@inner [$fallback [::core::clone::Clone::clone] $each $bounds] (lazy [$(#[$attr])*] $ident))
+    };
+    (@inner [$fallback:tt $clone:tt $each:tt $bounds:tt] (weak [$($attr:tt)*] $ident:ident)) => {
+        $($attr)* let $ident = match $crate::Upgrade::upgrade(&$ident) {
+            ::core::option::Option::Some($ident) => $ident,
+            ::core::option::Option::None => $fallback,
+        };
+    };
+    // The cached clone is borrowed from the closure, so it is cloned once
+    // more for values, such as futures, which outlive the call.
+    (@inner [$fallback:tt $clone:tt [each] $bounds:tt] (lazy [$($attr:tt)*] $ident:ident)) => {
+        $($attr)* let $ident = ::core::clone::Clone::clone($crate::Lazy::get(&$ident));
+    };
+    (@inner $cfg:tt (lazy [$($attr:tt)*] $ident:ident)) => {
+        $($attr)* let $ident = $crate::Lazy::get(&$ident);
+    };
+    // Captures which can only be produced once can't be cloned again on every
+    // call.
+    (@inner [$fallback:tt $clone:tt [each] $bounds:tt] (once $attrs:tt $mut:tt $ident:ident $init:tt [$form:literal])) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            $form,
+            ::core::stringify!($ident),
+            "` can't be cloned again every time the closure runs, so it can't be used with `each`",
+        ));
+    };
+    (@inner [$fallback:tt $clone:tt [each] $bounds:tt] (pat [$($attr:tt)*] [$pat:pat] [$src:expr])) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($pat),
+            "` is a destructuring pattern, so it can't be cloned again every time the closure runs with `each`",
+        ));
+    };
+    (@inner [$fallback:tt [$($clone:tt)*] [each] $bounds:tt] ($kind:ident [$($attr:tt)*] [$($mut:tt)?] $ident:ident $($desc:tt)*)) => {
+        $($attr)* let $($mut)? $ident = $($clone)*(&$ident);
+    };
+    (@inner $cfg:tt $desc:tt) => {};
+
+    // When captures are cloned again every time the closure runs, only the
+    // inner bindings need to be mutable.
+    (@binding [] [$($mut:tt)?] $ident:ident) => {
+        $($mut)? $ident
+    };
+    (@binding [each] $mut:tt $ident:ident) => {
+        $ident
+    };
+
+    (@detached $cfg:tt (words $(#[$attr:meta])* $prefix:ident self)) => {};
+    (@detached $cfg:tt (words $(#[$attr:meta])* weak $ident:ident)) => {
+        $crate::clone!(@detached $cfg (weak [$(#[$attr])*] $ident))
+    };
+    (@detached [$fallback:tt [::core::clone::Clone::clone] $each:tt $bounds:tt] (words $(#[$attr:meta])* lazy $ident:ident)) => {
+        $crate::clone!(@detached [$fallback [::core::clone::Clone::clone] $each $bounds] (lazy [$(#[$attr])*] $ident))
+    };
+    (@detached $cfg:tt (words $($item:tt)*)) => {};
+    (@detached $cfg:tt ($kind:ident $attrs:tt $ident:ident)) => {
+        ::core::compile_error!(::core::concat!(
+            "`",
+            ::core::stringify!($kind $ident),
+            "` can only be captured by a closure or `async` block",
+        ));
+    };
+    (@detached $cfg:tt $desc:tt) => {};
+
+    // Each capture is checked against the bounds requested with `send`,
+    // `sync` and `static` on its own, rather than just the whole closure.
+    // rustc points errors raised in the expansion of another crate's macro at
+    // the whole invocation, so the capture is named by wrapping its type in a
+    // `Capture` along with a struct of the same name instead.
+    (@assert $cfg:tt ($kind:ident $attrs:tt $ident:ident)) => {
+        $crate::clone!(@assert $cfg ($kind $attrs [] $ident))
+    };
+    (@assert [$fallback:tt $clone:tt $each:tt [$($bound:ident)*]] ($kind:ident $attrs:tt $mut:tt $ident:ident $($desc:tt)*)) => {
+        $($crate::clone!(@bound $bound $attrs $ident);)*
+    };
+    (@assert $cfg:tt $desc:tt) => {};
+
+    (@bound send [$($attr:tt)*] $ident:ident) => {
+        $($attr)* {
+            #[allow(dead_code, non_camel_case_types)]
+            struct $ident {}
+
+            $crate::__private::assert_send::<$ident, _>(&$ident);
+        }
+    };
+    (@bound sync [$($attr:tt)*] $ident:ident) => {
+        $($attr)* {
+            #[allow(dead_code, non_camel_case_types)]
+            struct $ident {}
+
+            $crate::__private::assert_sync::<$ident, _>(&$ident);
+        }
+    };
+    (@bound static [$($attr:tt)*] $ident:ident) => {
+        $($attr)* {
+            #[allow(dead_code, non_camel_case_types)]
+            struct $ident {}
+
+            $crate::__private::assert_static::<$ident, _>(&$ident);
+        }
+    };
+
+    (@build $cfg:tt [$($desc:tt)*]) => {
+        $($crate::clone!(@outer $cfg $desc);)*
+    };
+    (@build [$fallback:tt $clone:tt $each:tt []] [$($desc:tt)*] $($body:tt)+) => {{
+        $($crate::clone!(@outer [$fallback $clone $each []] $desc);)*
+
+        $crate::clone!(@body [$fallback $clone $each []] [$($desc)*] $($body)+)
+    }};
+    (@build $cfg:tt [$($desc:tt)*] $($body:tt)+) => {{
+        $crate::clone!(@build $cfg [$($desc)*]);
+
+        let value = $crate::clone!(@body $cfg [$($desc)*] $($body)+);
+        $crate::clone!(@assert $cfg (let [] [] value [value]));
+        value
+    }};
+
+    // The body is picked apart just enough to be able to inject the `@inner`
+    // statements at the start of closures and `async` blocks. Both are always
+    // made `move`, otherwise they would borrow the clones made by `@outer`,
+    // which are dropped at the end of the block wrapping the body.
+    (@body $cfg:tt $descs:tt $(move)? || $($body:tt)+) => {
+        $crate::clone!(@closure $cfg $descs [move] [] $($body)+)
+    };
+    (@body $cfg:tt $descs:tt $(move)? | $($tt:tt)+) => {
+        $crate::clone!(@args $cfg $descs [move] [] $($tt)+)
+    };
+    (@body $cfg:tt [$($desc:tt)*] async $(move)? $block:block $(,)?) => {
+        async move {
+            $($crate::clone!(@inner $cfg $desc);)*
+
+            $block
+        }
+    };
+    (@body [$fallback:tt $clone:tt [each] $bounds:tt] $descs:tt $expr:expr $(,)?) => {
+        ::core::compile_error!("`each` can only be used when the body is a closure or `async` block")
+    };
+    (@body $cfg:tt [$($desc:tt)*] $expr:expr $(,)?) => {{
+        $($crate::clone!(@detached $cfg $desc);)*
 
         $expr
     }};
-    ($(,)? mut { $expr:expr } as $ident:ident $($tt:tt)*) => {
-        let mut $ident = ::core::clone::Clone::clone(&$expr);
-        clone!($($tt)*);
+
+    (@args $cfg:tt $descs:tt $prefix:tt [$($arg:tt)*] | $($body:tt)+) => {
+        $crate::clone!(@closure $cfg $descs $prefix [$($arg)*] $($body)+)
+    };
+    (@args $cfg:tt $descs:tt $prefix:tt [$($arg:tt)*] $next:tt $($tt:tt)+) => {
+        $crate::clone!(@args $cfg $descs $prefix [$($arg)* $next] $($tt)+)
+    };
+
+    (@closure $cfg:tt [$($desc:tt)*] [$($prefix:tt)*] [$($arg:tt)*] -> $ret:ty $block:block $(,)?) => {
+        $($prefix)* |$($arg)*| -> $ret {
+            $($crate::clone!(@inner $cfg $desc);)*
+
+            $block
+        }
     };
-    ($(,)? mut $ident:ident $($tt:tt)*) => {
-        let mut $ident = ::core::clone::Clone::clone(&$ident);
-        clone!($($tt)*);
+    (@closure $cfg:tt [$($desc:tt)*] [$($prefix:tt)*] [$($arg:tt)*] $body:expr $(,)?) => {
+        $($prefix)* |$($arg)*| {
+            $($crate::clone!(@inner $cfg $desc);)*
+
+            $body
+        }
     };
-    ($(,)? { $expr:expr } as $ident:ident $($tt:tt)*) => {
-        let $ident = ::core::clone::Clone::clone(&$expr);
-        clone!($($tt)*);
+
+    (@$rule:ident $($tt:tt)*) => {
+        ::core::compile_error!("unexpected tokens in `clone!` invocation")
     };
-    ($(,)? $ident:ident $($tt:tt)*) => {
-        let $ident = ::core::clone::Clone::clone(&$ident);
-        clone!($($tt)*);
+    ($($tt:tt)*) => {
+        $crate::clone!(@mode [{ return } [::core::clone::Clone::clone] [] []] [$($tt)*] $($tt)*)
     };
-    ($(,)?) => {};
 }
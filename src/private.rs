@@ -0,0 +1,45 @@
+//! Implementation details of `clone!`, which are not part of the public API.
+
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+/// Used by the `future` mode.
+pub fn pin_future<F>(future: F) -> Pin<Box<dyn Future<Output = F::Output>>>
+where
+    F: Future + 'static,
+{
+    Box::pin(future)
+}
+
+/// Used by the `future + Send` mode.
+pub fn pin_send_future<F>(future: F) -> Pin<Box<dyn Future<Output = F::Output> + Send>>
+where
+    F: Future + Send + 'static,
+{
+    Box::pin(future)
+}
+
+/// Wraps the type of a capture checked by the `send`, `sync` and `static`
+/// modes, where `Name` is a struct named after the capture, so that errors
+/// name it, as in "within `Capture<name, Rc<String>>`".
+pub struct Capture<Name, T: ?Sized>(PhantomData<Name>, T);
+
+/// Used by the `send` mode.
+pub fn assert_send<Name, T: ?Sized>(_: &T)
+where
+    Capture<Name, T>: Send,
+{
+}
+
+/// Used by the `sync` mode.
+pub fn assert_sync<Name, T: ?Sized>(_: &T)
+where
+    Capture<Name, T>: Sync,
+{
+}
+
+/// Used by the `static` mode.
+pub fn assert_static<Name, T: ?Sized>(_: &T)
+where
+    Capture<Name, T>: 'static,
+{
+}
@@ -0,0 +1,49 @@
+//! The [`TryClone`] trait used by the `try` capture form.
+
+use std::{
+    fs::File,
+    io,
+    net::{TcpListener, TcpStream, UdpSocket},
+};
+
+/// Types which can only be duplicated fallibly, such as files and sockets.
+///
+/// `clone!` uses this trait for `try` captures, propagating the error from
+/// the enclosing function with `?`.
+pub trait TryClone: Sized {
+    /// The error returned when duplicating the value fails.
+    type Error;
+
+    /// Attempts to duplicate the value.
+    fn try_clone(&self) -> Result<Self, Self::Error>;
+}
+
+macro_rules! impl_try_clone {
+    ($($(#[$attr:meta])* $ty:ty),* $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl TryClone for $ty {
+                type Error = io::Error;
+
+                fn try_clone(&self) -> io::Result<Self> {
+                    <$ty>::try_clone(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_clone!(
+    File,
+    TcpListener,
+    TcpStream,
+    UdpSocket,
+    #[cfg(unix)]
+    std::os::fd::OwnedFd,
+    #[cfg(unix)]
+    std::os::unix::net::UnixDatagram,
+    #[cfg(unix)]
+    std::os::unix::net::UnixListener,
+    #[cfg(unix)]
+    std::os::unix::net::UnixStream,
+);
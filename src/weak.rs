@@ -0,0 +1,58 @@
+//! Traits used by the `weak` capture form.
+
+use std::{rc, sync};
+
+/// Types which can be downgraded into a weak reference, such as [`Rc`](std::rc::Rc)
+/// and [`Arc`](std::sync::Arc).
+///
+/// `clone!` uses this trait for `weak` captures so that the same syntax works
+/// regardless of which pointer type is being captured.
+pub trait Downgrade {
+    /// The weak counterpart of this pointer.
+    type Weak: Upgrade<Strong = Self>;
+
+    /// Creates a new weak reference to the pointee.
+    fn downgrade(&self) -> Self::Weak;
+}
+
+/// Weak references which can be upgraded back into a strong reference.
+pub trait Upgrade {
+    /// The strong counterpart of this weak reference.
+    type Strong;
+
+    /// Attempts to upgrade this weak reference, returning `None` if the
+    /// value has already been dropped.
+    fn upgrade(&self) -> Option<Self::Strong>;
+}
+
+impl<T: ?Sized> Downgrade for rc::Rc<T> {
+    type Weak = rc::Weak<T>;
+
+    fn downgrade(&self) -> Self::Weak {
+        rc::Rc::downgrade(self)
+    }
+}
+
+impl<T: ?Sized> Upgrade for rc::Weak<T> {
+    type Strong = rc::Rc<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        rc::Weak::upgrade(self)
+    }
+}
+
+impl<T: ?Sized> Downgrade for sync::Arc<T> {
+    type Weak = sync::Weak<T>;
+
+    fn downgrade(&self) -> Self::Weak {
+        sync::Arc::downgrade(self)
+    }
+}
+
+impl<T: ?Sized> Upgrade for sync::Weak<T> {
+    type Strong = sync::Arc<T>;
+
+    fn upgrade(&self) -> Option<Self::Strong> {
+        sync::Weak::upgrade(self)
+    }
+}
@@ -0,0 +1,67 @@
+//! The `box`, `rc` and `arc` modes have to produce trait objects which can be
+//! stored and called like hand-wrapped closures.
+
+use clone_macro::clone;
+use std::{
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+#[test]
+fn boxed() {
+    let name = "Ferris".to_string();
+
+    let callbacks: Vec<Box<dyn Fn(usize) -> usize>> = vec![
+        clone!(box dyn Fn(usize) -> usize [name], move |n| name.len() + n),
+        clone!(box dyn Fn(usize) -> usize [name], move |n| name.len() * n),
+    ];
+
+    let results = callbacks.iter().map(|c| c(2)).collect::<Vec<_>>();
+
+    assert_eq!(results, [8, 12]);
+}
+
+#[test]
+fn rc() {
+    let name = Rc::new("Ferris".to_string());
+
+    let c: Rc<dyn Fn() -> usize> = clone!(rc dyn Fn() -> usize [name], move || name.len());
+    let other = Rc::clone(&c);
+
+    assert_eq!(c(), 6);
+    assert_eq!(other(), 6);
+    assert_eq!(Rc::strong_count(&name), 2);
+
+    drop((c, other));
+
+    assert_eq!(Rc::strong_count(&name), 1);
+}
+
+#[test]
+fn arc_sync() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    #[rustfmt::skip]
+    let c: Arc<dyn Fn() -> usize + Send + Sync> = clone!(sync arc dyn Fn() -> usize + Send + Sync [calls], move || {
+        calls.fetch_add(1, Ordering::SeqCst) + 1
+    });
+
+    let handles = (0..4)
+        .map(|_| {
+            let c = Arc::clone(&c);
+
+            thread::spawn(move || c())
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(c(), 5);
+    assert_eq!(calls.load(Ordering::SeqCst), 5);
+}
@@ -0,0 +1,61 @@
+//! The `future` modes have to produce pinned, boxed futures which resolve to
+//! the value of the wrapped block.
+
+use clone_macro::clone;
+use std::{
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+fn block_on<F: Future + ?Sized>(mut future: Pin<Box<F>>) -> F::Output {
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn local() {
+    let name = Rc::new("Ferris".to_string());
+
+    #[rustfmt::skip]
+    let future = clone!(future [name], async move { name.len() });
+
+    assert_eq!(Rc::strong_count(&name), 2);
+    assert_eq!(block_on(future), 6);
+    assert_eq!(Rc::strong_count(&name), 1);
+}
+
+#[test]
+fn send() {
+    let name = Arc::new("Ferris".to_string());
+
+    #[rustfmt::skip]
+    let future: Pin<Box<dyn Future<Output = usize> + Send>> = clone!(future + Send [name], async move {
+        name.len()
+    });
+
+    assert_eq!(block_on(future), 6);
+    assert_eq!(Arc::strong_count(&name), 1);
+}
+
+#[test]
+fn each_lazy() {
+    let words = Rc::new(vec!["lazy", "each"]);
+
+    #[rustfmt::skip]
+    let count = clone!(each [lazy words], move || async move { words.len() });
+
+    let first = Box::pin(count());
+    let second = Box::pin(count());
+
+    drop(count);
+
+    assert_eq!(block_on(first) + block_on(second), 4);
+}
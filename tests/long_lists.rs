@@ -0,0 +1,182 @@
+//! Capture lists are expanded without recursing once per capture, so even
+//! long lists which mix forms stay within the default `#![recursion_limit]`.
+
+use clone_macro::clone;
+use std::{array, cell::Cell, rc::Rc, sync::atomic::AtomicUsize};
+
+#[test]
+fn mixed_forms() {
+    let [p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15, p16, p17, p18, p19, p20, p21, p22, p23, p24, p25, p26, p27, p28, p29]: [String; 30] =
+        array::from_fn(|i| "p".repeat(i));
+    let [r0, r1, r2, r3, r4, r5, r6, r7, r8, r9, r10, r11, r12, r13, r14, r15, r16, r17, r18, r19, r20, r21, r22, r23, r24, r25, r26, r27, r28, r29]: [Vec<u8>; 30] =
+        array::from_fn(|i| vec![0; i]);
+    let [t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15, t16, t17, t18, t19, t20, t21, t22, t23, t24, t25, t26, t27, t28, t29]: [u32; 30] =
+        array::from_fn(|i| i as u32);
+    let [w0, w1, w2, w3, w4, w5, w6, w7, w8, w9, w10, w11, w12, w13, w14, w15, w16, w17, w18, w19, w20, w21, w22, w23, w24, w25, w26, w27, w28, w29]: [Rc<Cell<usize>>; 30] =
+        array::from_fn(|i| Rc::new(Cell::new(i)));
+    let [c0, c1, c2, c3, c4, c5, c6, c7, c8, c9, c10, c11, c12, c13, c14, c15, c16, c17, c18, c19, c20, c21, c22, c23, c24, c25, c26, c27, c28, c29]: [Rc<usize>; 30] =
+        array::from_fn(Rc::new);
+    let [n0, n1, n2, n3, n4, n5, n6, n7, n8, n9, n10, n11, n12, n13, n14, n15, n16, n17, n18, n19, n20, n21, n22, n23, n24, n25, n26, n27, n28, n29]: [String; 30] =
+        array::from_fn(|i| "n".repeat(i));
+    let [a0, a1, a2, a3, a4, a5, a6, a7, a8, a9, a10, a11, a12, a13, a14, a15, a16, a17, a18, a19, a20, a21, a22, a23, a24, a25, a26, a27, a28, a29]: [AtomicUsize; 30] =
+        array::from_fn(AtomicUsize::new);
+
+    let sum = clone!(
+        [
+            p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15, p16, p17, p18,
+            p19, p20, p21, p22, p23, p24, p25, p26, p27, p28, p29, &r0, &r1, &r2, &r3, &r4, &r5,
+            &r6, &r7, &r8, &r9, &r10, &r11, &r12, &r13, &r14, &r15, &r16, &r17, &r18, &r19,
+            &r20, &r21, &r22, &r23, &r24, &r25, &r26, &r27, &r28, &r29, t0: u64, t1: u64,
+            t2: u64, t3: u64, t4: u64, t5: u64, t6: u64, t7: u64, t8: u64, t9: u64, t10: u64,
+            t11: u64, t12: u64, t13: u64, t14: u64, t15: u64, t16: u64, t17: u64, t18: u64,
+            t19: u64, t20: u64, t21: u64, t22: u64, t23: u64, t24: u64, t25: u64, t26: u64,
+            t27: u64, t28: u64, t29: u64, @default-return 0, weak w0, weak w1, weak w2, weak w3,
+            weak w4, weak w5, weak w6, weak w7, weak w8, weak w9, weak w10, weak w11, weak w12,
+            weak w13, weak w14, weak w15, weak w16, weak w17, weak w18, weak w19, weak w20,
+            weak w21, weak w22, weak w23, weak w24, weak w25, weak w26, weak w27, weak w28,
+            weak w29, rc c0, rc c1, rc c2, rc c3, rc c4, rc c5, rc c6, rc c7, rc c8, rc c9,
+            rc c10, rc c11, rc c12, rc c13, rc c14, rc c15, rc c16, rc c17, rc c18, rc c19,
+            rc c20, rc c21, rc c22, rc c23, rc c24, rc c25, rc c26, rc c27, rc c28, rc c29,
+            n0 as o0, n1 as o1, n2 as o2, n3 as o3, n4 as o4, n5 as o5, n6 as o6, n7 as o7,
+            n8 as o8, n9 as o9, n10 as o10, n11 as o11, n12 as o12, n13 as o13, n14 as o14,
+            n15 as o15, n16 as o16, n17 as o17, n18 as o18, n19 as o19, n20 as o20, n21 as o21,
+            n22 as o22, n23 as o23, n24 as o24, n25 as o25, n26 as o26, n27 as o27, n28 as o28,
+            n29 as o29, load(Relaxed) a0, load(Relaxed) a1, load(Relaxed) a2, load(Relaxed) a3,
+            load(Relaxed) a4, load(Relaxed) a5, load(Relaxed) a6, load(Relaxed) a7,
+            load(Relaxed) a8, load(Relaxed) a9, load(Relaxed) a10, load(Relaxed) a11,
+            load(Relaxed) a12, load(Relaxed) a13, load(Relaxed) a14, load(Relaxed) a15,
+            load(Relaxed) a16, load(Relaxed) a17, load(Relaxed) a18, load(Relaxed) a19,
+            load(Relaxed) a20, load(Relaxed) a21, load(Relaxed) a22, load(Relaxed) a23,
+            load(Relaxed) a24, load(Relaxed) a25, load(Relaxed) a26, load(Relaxed) a27,
+            load(Relaxed) a28, load(Relaxed) a29,
+        ],
+        || {
+            p0.len() + p1.len() + p2.len() + p3.len() + p4.len() + p5.len() + p6.len()
+            + p7.len() + p8.len() + p9.len() + p10.len() + p11.len() + p12.len() + p13.len()
+            + p14.len() + p15.len() + p16.len() + p17.len() + p18.len() + p19.len() + p20.len()
+            + p21.len() + p22.len() + p23.len() + p24.len() + p25.len() + p26.len() + p27.len()
+            + p28.len() + p29.len() + r0.len() + r1.len() + r2.len() + r3.len() + r4.len()
+            + r5.len() + r6.len() + r7.len() + r8.len() + r9.len() + r10.len() + r11.len()
+            + r12.len() + r13.len() + r14.len() + r15.len() + r16.len() + r17.len() + r18.len()
+            + r19.len() + r20.len() + r21.len() + r22.len() + r23.len() + r24.len() + r25.len()
+            + r26.len() + r27.len() + r28.len() + r29.len() + t0 as usize + t1 as usize
+            + t2 as usize + t3 as usize + t4 as usize + t5 as usize + t6 as usize + t7 as usize
+            + t8 as usize + t9 as usize + t10 as usize + t11 as usize + t12 as usize
+            + t13 as usize + t14 as usize + t15 as usize + t16 as usize + t17 as usize
+            + t18 as usize + t19 as usize + t20 as usize + t21 as usize + t22 as usize
+            + t23 as usize + t24 as usize + t25 as usize + t26 as usize + t27 as usize
+            + t28 as usize + t29 as usize + w0.get() + w1.get() + w2.get() + w3.get() + w4.get()
+            + w5.get() + w6.get() + w7.get() + w8.get() + w9.get() + w10.get() + w11.get()
+            + w12.get() + w13.get() + w14.get() + w15.get() + w16.get() + w17.get() + w18.get()
+            + w19.get() + w20.get() + w21.get() + w22.get() + w23.get() + w24.get() + w25.get()
+            + w26.get() + w27.get() + w28.get() + w29.get() + *c0 + *c1 + *c2 + *c3 + *c4 + *c5
+            + *c6 + *c7 + *c8 + *c9 + *c10 + *c11 + *c12 + *c13 + *c14 + *c15 + *c16 + *c17
+            + *c18 + *c19 + *c20 + *c21 + *c22 + *c23 + *c24 + *c25 + *c26 + *c27 + *c28 + *c29
+            + o0.len() + o1.len() + o2.len() + o3.len() + o4.len() + o5.len() + o6.len()
+            + o7.len() + o8.len() + o9.len() + o10.len() + o11.len() + o12.len() + o13.len()
+            + o14.len() + o15.len() + o16.len() + o17.len() + o18.len() + o19.len() + o20.len()
+            + o21.len() + o22.len() + o23.len() + o24.len() + o25.len() + o26.len() + o27.len()
+            + o28.len() + o29.len() + a0 + a1 + a2 + a3 + a4 + a5 + a6 + a7 + a8 + a9 + a10
+            + a11 + a12 + a13 + a14 + a15 + a16 + a17 + a18 + a19 + a20 + a21 + a22 + a23 + a24
+            + a25 + a26 + a27 + a28 + a29
+        }
+    );
+
+    assert_eq!(sum(), 7 * (0..30).sum::<usize>());
+
+    drop(w0);
+
+    assert_eq!(sum(), 0);
+}
+
+#[test]
+fn indexes_and_patterns() {
+    let [i0, i1, i2, i3, i4, i5, i6, i7, i8, i9, i10, i11, i12, i13, i14, i15, i16, i17, i18, i19, i20, i21, i22, i23, i24, i25, i26, i27, i28, i29, i30, i31, i32, i33, i34, i35, i36, i37, i38, i39, i40, i41, i42, i43, i44, i45, i46, i47, i48, i49, i50, i51, i52, i53, i54, i55, i56, i57, i58, i59, i60, i61, i62, i63, i64, i65, i66, i67, i68, i69, i70, i71, i72, i73, i74, i75, i76, i77, i78, i79, i80, i81, i82, i83, i84, i85, i86, i87, i88, i89, i90, i91, i92, i93, i94, i95, i96, i97, i98, i99, i100, i101, i102, i103, i104, i105, i106, i107, i108, i109, i110, i111, i112, i113, i114, i115, i116, i117, i118, i119, i120, i121, i122, i123, i124, i125, i126, i127, i128, i129]: [usize; 130] =
+        array::from_fn(|i| i);
+    let values = (1..=3).collect::<Vec<usize>>();
+    let pair = (4, "four");
+    let list = [5, 6, 7];
+
+    let sum = clone!(
+        [
+            values[0] as first,
+            { pair } as (number, _),
+            { list } as [head, ..],
+            i0, i1, i2, i3, i4, i5, i6, i7, i8, i9, i10, i11, i12, i13, i14, i15, i16,
+            i17, i18, i19, i20, i21, i22, i23, i24, i25, i26, i27, i28, i29, i30, i31,
+            i32, i33, i34, i35, i36, i37, i38, i39, i40, i41, i42, i43, i44, i45, i46,
+            i47, i48, i49, i50, i51, i52, i53, i54, i55, i56, i57, i58, i59, i60, i61,
+            i62, i63, i64, i65, i66, i67, i68, i69, i70, i71, i72, i73, i74, i75, i76,
+            i77, i78, i79, i80, i81, i82, i83, i84, i85, i86, i87, i88, i89, i90, i91,
+            i92, i93, i94, i95, i96, i97, i98, i99, i100, i101, i102, i103, i104, i105,
+            i106, i107, i108, i109, i110, i111, i112, i113, i114, i115, i116, i117,
+            i118, i119, i120, i121, i122, i123, i124, i125, i126, i127, i128, i129,
+        ],
+        || {
+            i0 + i1 + i2 + i3 + i4 + i5 + i6 + i7 + i8 + i9 + i10 + i11 + i12 + i13 + i14 + i15
+            + i16 + i17 + i18 + i19 + i20 + i21 + i22 + i23 + i24 + i25 + i26 + i27 + i28 + i29
+            + i30 + i31 + i32 + i33 + i34 + i35 + i36 + i37 + i38 + i39 + i40 + i41 + i42 + i43
+            + i44 + i45 + i46 + i47 + i48 + i49 + i50 + i51 + i52 + i53 + i54 + i55 + i56 + i57
+            + i58 + i59 + i60 + i61 + i62 + i63 + i64 + i65 + i66 + i67 + i68 + i69 + i70 + i71
+            + i72 + i73 + i74 + i75 + i76 + i77 + i78 + i79 + i80 + i81 + i82 + i83 + i84 + i85
+            + i86 + i87 + i88 + i89 + i90 + i91 + i92 + i93 + i94 + i95 + i96 + i97 + i98 + i99
+            + i100 + i101 + i102 + i103 + i104 + i105 + i106 + i107 + i108 + i109 + i110 + i111
+            + i112 + i113 + i114 + i115 + i116 + i117 + i118 + i119 + i120 + i121 + i122 + i123
+            + i124 + i125 + i126 + i127 + i128 + i129
+            + first + number + head
+        }
+    );
+
+    assert_eq!(sum(), (0..130).sum::<usize>() + 1 + 4 + 5);
+}
+
+#[test]
+fn derefs_tuple_indices_and_struct_patterns() {
+    #[derive(Clone)]
+    struct Config {
+        port: usize,
+    }
+
+    let [i0, i1, i2, i3, i4, i5, i6, i7, i8, i9, i10, i11, i12, i13, i14, i15, i16, i17, i18, i19, i20, i21, i22, i23, i24, i25, i26, i27, i28, i29, i30, i31, i32, i33, i34, i35, i36, i37, i38, i39, i40, i41, i42, i43, i44, i45, i46, i47, i48, i49, i50, i51, i52, i53, i54, i55, i56, i57, i58, i59, i60, i61, i62, i63, i64, i65, i66, i67, i68, i69, i70, i71, i72, i73, i74, i75, i76, i77, i78, i79, i80, i81, i82, i83, i84, i85, i86, i87, i88, i89, i90, i91, i92, i93, i94, i95, i96, i97, i98, i99, i100, i101, i102, i103, i104, i105, i106, i107, i108, i109, i110, i111, i112, i113, i114, i115, i116, i117, i118, i119, i120, i121, i122, i123, i124, i125, i126, i127, i128, i129, i130, i131, i132, i133, i134, i135, i136, i137, i138, i139, i140, i141, i142, i143, i144, i145, i146, i147, i148, i149]: [usize; 150] =
+        array::from_fn(|i| i);
+    let pair = (1, "one");
+    let boxed = Box::new(2);
+    let config = Config { port: 3 };
+
+    let sum = clone!(
+        [
+            pair.0 as first,
+            *boxed as inner,
+            { config } as Config { port },
+            config as Config { port: other_port },
+            { pair.1 } as name,
+            i0, i1, i2, i3, i4, i5, i6, i7, i8, i9, i10, i11, i12, i13, i14, i15, i16, i17, i18,
+            i19, i20, i21, i22, i23, i24, i25, i26, i27, i28, i29, i30, i31, i32, i33, i34, i35,
+            i36, i37, i38, i39, i40, i41, i42, i43, i44, i45, i46, i47, i48, i49, i50, i51, i52,
+            i53, i54, i55, i56, i57, i58, i59, i60, i61, i62, i63, i64, i65, i66, i67, i68, i69,
+            i70, i71, i72, i73, i74, i75, i76, i77, i78, i79, i80, i81, i82, i83, i84, i85, i86,
+            i87, i88, i89, i90, i91, i92, i93, i94, i95, i96, i97, i98, i99, i100, i101, i102, i103,
+            i104, i105, i106, i107, i108, i109, i110, i111, i112, i113, i114, i115, i116, i117,
+            i118, i119, i120, i121, i122, i123, i124, i125, i126, i127, i128, i129, i130, i131,
+            i132, i133, i134, i135, i136, i137, i138, i139, i140, i141, i142, i143, i144, i145,
+            i146, i147, i148, i149,
+        ],
+        || {
+            i0 + i1 + i2 + i3 + i4 + i5 + i6 + i7 + i8 + i9 + i10 + i11 + i12 + i13 + i14 + i15
+            + i16 + i17 + i18 + i19 + i20 + i21 + i22 + i23 + i24 + i25 + i26 + i27 + i28 + i29
+            + i30 + i31 + i32 + i33 + i34 + i35 + i36 + i37 + i38 + i39 + i40 + i41 + i42 + i43
+            + i44 + i45 + i46 + i47 + i48 + i49 + i50 + i51 + i52 + i53 + i54 + i55 + i56 + i57
+            + i58 + i59 + i60 + i61 + i62 + i63 + i64 + i65 + i66 + i67 + i68 + i69 + i70 + i71
+            + i72 + i73 + i74 + i75 + i76 + i77 + i78 + i79 + i80 + i81 + i82 + i83 + i84 + i85
+            + i86 + i87 + i88 + i89 + i90 + i91 + i92 + i93 + i94 + i95 + i96 + i97 + i98 + i99
+            + i100 + i101 + i102 + i103 + i104 + i105 + i106 + i107 + i108 + i109 + i110 + i111
+            + i112 + i113 + i114 + i115 + i116 + i117 + i118 + i119 + i120 + i121 + i122 + i123
+            + i124 + i125 + i126 + i127 + i128 + i129 + i130 + i131 + i132 + i133 + i134 + i135
+            + i136 + i137 + i138 + i139 + i140 + i141 + i142 + i143 + i144 + i145 + i146 + i147
+            + i148 + i149
+            + first + inner + port + other_port + name.len()
+        }
+    );
+
+    assert_eq!(sum(), (0..150).sum::<usize>() + 1 + 2 + 3 + 3 + 3);
+}
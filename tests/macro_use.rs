@@ -0,0 +1,16 @@
+//! `clone!` brought into scope with `#[macro_use]` instead of an import.
+
+#[macro_use]
+extern crate clone_macro;
+
+#[test]
+fn macro_use() {
+    let name = "Ferris".to_string();
+    let scores = vec![1, 2, 3];
+
+    let c = clone!([name, &scores, { scores[0] } as first], move || {
+        name.len() + scores.len() + first
+    });
+
+    assert_eq!(c(), 10);
+}
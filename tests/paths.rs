@@ -0,0 +1,133 @@
+//! `clone!` has to keep working no matter how it is brought into scope, since
+//! it calls itself recursively.
+
+mod imported {
+    use clone_macro::clone;
+
+    #[test]
+    fn use_import() {
+        let name = "Ferris".to_string();
+
+        let c = clone!([name], move || name.len());
+
+        assert_eq!(c(), 6);
+    }
+}
+
+mod glob {
+    mod other {
+        #[allow(unused_macros)]
+        macro_rules! clone {
+            ($($tt:tt)*) => {
+                ::core::compile_error!("expanded the wrong `clone!`")
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use clone;
+    }
+
+    // The only `clone!` in scope here is the wrong one, brought in by a glob
+    // import.
+    #[allow(unused_imports)]
+    use other::*;
+
+    #[test]
+    fn glob_imported_clone() {
+        let name = "Ferris".to_string();
+        let count = 1;
+
+        let mut c = clone_macro::clone!([name, mut count], move || {
+            count += 1;
+
+            name.len() + count
+        });
+
+        assert_eq!(c(), 8);
+    }
+}
+
+mod fully_qualified {
+    use std::{cell::Cell, rc::Rc};
+
+    // Shadows any `clone!` the expansion might refer to without a path.
+    #[allow(unused_macros)]
+    macro_rules! clone {
+        ($($tt:tt)*) => {
+            ::core::compile_error!("expanded the wrong `clone!`")
+        };
+    }
+
+    #[test]
+    fn list() {
+        let name = "Ferris".to_string();
+        let count = 1;
+
+        let mut c = clone_macro::clone!([name, mut count], move || {
+            count += 1;
+
+            name.len() + count
+        });
+
+        assert_eq!(c(), 8);
+    }
+
+    #[test]
+    fn without_list() {
+        let name = "Ferris".to_string();
+
+        {
+            clone_macro::clone!(mut name);
+
+            name.push('!');
+
+            assert_eq!(name, "Ferris!");
+        }
+
+        assert_eq!(name, "Ferris");
+    }
+
+    #[test]
+    fn recursive_forms() {
+        let name = "Ferris".to_string();
+        let counter = Rc::new(Cell::new(0));
+        let scores = vec![1, 2, 3];
+
+        let c = clone_macro::clone!(
+            [
+                #[allow(unused_mut)]
+                mut name as greeting,
+                &scores,
+                weak counter,
+                { scores[0] } as first,
+                @default-return 0,
+            ],
+            |times: usize| {
+                counter.set(counter.get() + times);
+
+                greeting.len() + scores.len() + first
+            },
+        );
+
+        assert_eq!(c(2), 10);
+        assert_eq!(counter.get(), 2);
+    }
+
+    #[test]
+    fn modes() {
+        let name = "Ferris".to_string();
+
+        let c = clone_macro::clone!(each send static box dyn Fn() -> usize + Send [name], || {
+            name.len()
+        });
+
+        assert_eq!(c(), 6);
+
+        // rustfmt would rewrite `Send [name]` as `Send[name]`, as if it were
+        // indexing; keep it the way the docs write it.
+        #[rustfmt::skip]
+        let future = clone_macro::clone!(future + Send [name], async { name.len() });
+
+        drop(future);
+    }
+}